@@ -0,0 +1,242 @@
+//! Python bindings of lg, built with maturin:
+//!
+//! ```python
+//! import lg
+//!
+//! for header in lg.find("/data", where="point_format in [6,7]", recursive=True):
+//!     print(header["path"], header["number_of_points"])
+//! ```
+
+// The code generated by the pyo3 macros converts the errors to themselves
+#![allow(clippy::useless_conversion)]
+
+use std::path::Path;
+
+use lg::filter::Compare;
+use lg::info::header_json;
+use lg::tile_name::TileNames;
+use lg::{LasHeaderFilter, MissingPolicy, MyHeader, WalkOptions};
+use pyo3::exceptions::{PyIOError, PyRuntimeWarning, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+
+/// Converts a JSON value to the equivalent Python object.
+fn to_python(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(value) => value.into_py(py),
+        Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(value), _) => value.into_py(py),
+            (None, Some(value)) => value.into_py(py),
+            (None, None) => number.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(value) => value.into_py(py),
+        Value::Array(values) => {
+            let list = PyList::empty_bound(py);
+            for value in values {
+                list.append(to_python(py, value)?)?;
+            }
+            list.into_py(py)
+        }
+        Value::Object(object) => {
+            let dict = PyDict::new_bound(py);
+            for (key, value) in object {
+                dict.set_item(key, to_python(py, value)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+/// Header of a file as a dict, with the same keys as `lg info --format json`.
+fn header_record(py: Python<'_>, path: &Path, header: &MyHeader) -> PyResult<PyObject> {
+    to_python(py, &header_json(&path.display().to_string(), header))
+}
+
+/// Filter of headers, parsed from a query (eg. "point_format in [6,7] and date >=2023").
+#[pyclass(name = "Filter", module = "lg")]
+struct PyFilter {
+    filter: LasHeaderFilter,
+}
+
+impl PyFilter {
+    fn parse(query: &str, missing: &str) -> PyResult<LasHeaderFilter> {
+        let mut filter = query
+            .parse::<LasHeaderFilter>()
+            .map_err(PyValueError::new_err)?;
+        filter.missing_policy = missing
+            .parse::<MissingPolicy>()
+            .map_err(PyValueError::new_err)?;
+        Ok(filter)
+    }
+
+    /// Fails on the filters on name fields, which only [`find`] captures.
+    fn check_names(&self) -> PyResult<()> {
+        match self.filter.needs_names() {
+            true => Err(PyValueError::new_err(
+                "the filters on name fields need find(name_pattern=...)",
+            )),
+            false => Ok(()),
+        }
+    }
+}
+
+#[pymethods]
+impl PyFilter {
+    /// `missing` is the policy of the filters on values missing from the header: "match",
+    /// "skip" or "error", as the --missing option.
+    #[new]
+    #[pyo3(signature = (query, missing = "match"))]
+    fn new(query: &str, missing: &str) -> PyResult<Self> {
+        Ok(PyFilter {
+            filter: PyFilter::parse(query, missing)?,
+        })
+    }
+
+    /// Reads the header of a file and tells if it is selected. The points are all read when
+    /// the filters compare them.
+    fn matches(&self, path: &str) -> PyResult<bool> {
+        self.check_names()?;
+        self.filter
+            .filter_path(Path::new(path))
+            .map_err(PyIOError::new_err)
+    }
+
+    /// Evaluates the filters on a file, as --explain: a list of (filter, value, verdict).
+    fn evaluate(&self, path: &str) -> PyResult<Vec<(String, String, String)>> {
+        self.check_names()?;
+        let header = self
+            .filter
+            .read_header(Path::new(path))
+            .map_err(PyIOError::new_err)?;
+        Ok(self
+            .filter
+            .evaluate(&header)
+            .into_iter()
+            .map(|(filter, verdict)| {
+                (
+                    filter.to_string(),
+                    filter.header_value(&header),
+                    verdict.to_string(),
+                )
+            })
+            .collect())
+    }
+
+    fn __str__(&self) -> String {
+        self.filter.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("lg.Filter({:?})", self.filter.to_string())
+    }
+}
+
+/// Condition of [`find`], a query or a [`PyFilter`].
+#[derive(FromPyObject)]
+enum Where<'py> {
+    Query(String),
+    Filter(PyRef<'py, PyFilter>),
+}
+
+/// Walks a folder and returns the headers of the selected files, as dicts.
+///
+/// The files which can't be read are skipped with a RuntimeWarning, as lg reports them on
+/// stderr.
+#[pyfunction]
+#[pyo3(signature = (
+    path,
+    r#where = None,
+    recursive = false,
+    follow_links = false,
+    invert = false,
+    extensions = None,
+    missing = "match",
+    scan_points = None,
+    name_pattern = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn find(
+    py: Python<'_>,
+    path: &str,
+    r#where: Option<Where<'_>>,
+    recursive: bool,
+    follow_links: bool,
+    invert: bool,
+    extensions: Option<Vec<String>>,
+    missing: &str,
+    scan_points: Option<u64>,
+    name_pattern: Option<&str>,
+) -> PyResult<Vec<PyObject>> {
+    let parsed;
+    let filter = match &r#where {
+        Some(Where::Filter(filter)) => &filter.filter,
+        Some(Where::Query(query)) => {
+            parsed = PyFilter::parse(query, missing)?;
+            &parsed
+        }
+        None => {
+            parsed = PyFilter::parse("", missing)?;
+            &parsed
+        }
+    };
+    if scan_points == Some(0) {
+        return Err(PyValueError::new_err("scan_points should be at least 1"));
+    }
+    if filter.needs_points() && scan_points.is_none() {
+        return Err(PyValueError::new_err(
+            "the filters on points need scan_points",
+        ));
+    }
+    let names = name_pattern
+        .map(TileNames::new)
+        .transpose()
+        .map_err(PyValueError::new_err)?;
+    if filter.needs_names() && names.is_none() {
+        return Err(PyValueError::new_err(
+            "the filters on name fields need name_pattern",
+        ));
+    }
+    let default = WalkOptions::default();
+    let options = WalkOptions {
+        recursive,
+        follow_links,
+        inverse: invert,
+        extensions: extensions.map_or(default.extensions, |extensions| {
+            extensions.iter().map(|e| e.to_lowercase()).collect()
+        }),
+        scan_points,
+        validate: filter.needs_validation(),
+        sidecars: filter.needs_sidecars(),
+        names,
+    };
+
+    let results = py.allow_threads(|| lg::find(path, &options, filter).collect::<Vec<_>>());
+
+    let warning = py.get_type_bound::<PyRuntimeWarning>();
+    let mut records = Vec::new();
+    for result in results {
+        match result {
+            Ok(file) => records.push(header_record(py, &file.path, &file.header)?),
+            Err(error) => PyErr::warn_bound(py, &warning, &error, 1)?,
+        }
+    }
+    Ok(records)
+}
+
+/// Reads the header of a las or laz file, as a dict.
+#[pyfunction]
+fn read_header(py: Python<'_>, path: &str) -> PyResult<PyObject> {
+    let header = MyHeader::from_path(Path::new(path)).map_err(PyIOError::new_err)?;
+    header_record(py, Path::new(path), &header)
+}
+
+#[pymodule]
+#[pyo3(name = "lg")]
+fn lg_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyFilter>()?;
+    m.add_function(wrap_pyfunction!(find, m)?)?;
+    m.add_function(wrap_pyfunction!(read_header, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::filter::Compare;
+use crate::header::MyHeader;
+use std::fmt::{Display, Formatter};
+
+#[derive(PartialEq, Debug)]
+pub struct BoolFilter {
+    value: bool,
+    header_attribute: String,
+}
+
+impl BoolFilter {
+    pub fn new(value: bool, attribute_name: &str) -> BoolFilter {
+        BoolFilter {
+            value,
+            header_attribute: attribute_name.to_string(),
+        }
+    }
+}
+
+impl Compare for BoolFilter {
+    fn attribute(&self) -> &str {
+        &self.header_attribute
+    }
+
+    fn header_value(&self, value: &MyHeader) -> String {
+        MyHeader::bool_field(&self.header_attribute)
+            .map_or(String::from("-"), |accessor| accessor(value).to_string())
+    }
+
+    fn compare(&self, value: &MyHeader) -> bool {
+        match MyHeader::bool_field(&self.header_attribute) {
+            Some(accessor) => accessor(value) == self.value,
+            None => false,
+        }
+    }
+}
+
+impl Display for BoolFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.header_attribute, self.value)
+    }
+}
+
+#[test]
+fn test_point_format_properties() {
+    let mut builder = las::Builder::from((1, 4));
+    builder.point_format = las::point::Format::new(8).unwrap();
+    builder.point_format.extra_bytes = 4;
+    let header = MyHeader::new(&builder.into_header().unwrap());
+
+    assert!(BoolFilter::new(true, "has_gps_time").compare(&header));
+    assert!(BoolFilter::new(true, "has_nir").compare(&header));
+    assert!(BoolFilter::new(true, "extended").compare(&header));
+    assert!(BoolFilter::new(false, "has_waveform").compare(&header));
+    assert!(!BoolFilter::new(false, "has_color").compare(&header));
+    assert_eq!(header.point_data_record_length, 42);
+}
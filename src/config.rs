@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use clap::{Arg, ArgAction, Command};
+use serde::Deserialize;
+use toml::{Table, Value};
+
+/// Name of the project configuration file, searched in the current directory and its parents.
+pub const PROJECT_CONFIG: &str = ".lg.toml";
+
+/// Default options and named queries, read from the configuration files.
+///
+/// Keys are the long names of the command line options (eg. `generating-software`), values
+/// are strings, numbers, booleans (for flags) or arrays (for repeated or delimited options):
+///
+/// ```toml
+/// [defaults]
+/// extensions = ["laz"]
+///
+/// [queries.delivery-check]
+/// wkt = "POLYGON((...))"
+/// generating-software = "TerraScan.*"
+/// recursive = true
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub defaults: Table,
+    #[serde(default)]
+    pub queries: BTreeMap<String, Table>,
+}
+
+impl Config {
+    /// Reads a configuration file, a missing file giving an empty configuration.
+    pub fn from_file(path: &Path) -> Result<Config, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .map_err(|error| format!("Invalid configuration {}: {error}", path.display())),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(error) => Err(format!("Unable to read {}: {error}", path.display())),
+        }
+    }
+
+    /// Reads the user configuration, then the project one which overrides it.
+    pub fn load() -> Result<Config, String> {
+        let mut config = Config::default();
+        if let Some(path) = user_config_path() {
+            config.merge(Config::from_file(&path)?);
+        }
+        if let Some(path) = project_config_path() {
+            config.merge(Config::from_file(&path)?);
+        }
+        Ok(config)
+    }
+
+    /// Overrides the defaults and the queries with the ones of `other`.
+    pub fn merge(&mut self, other: Config) {
+        self.defaults.extend(other.defaults);
+        self.queries.extend(other.queries);
+    }
+
+    /// Inserts the options of the named queries (`@name`) and the defaults in the command
+    /// line arguments. Options given on the command line take precedence over the queries,
+    /// which take precedence over the defaults.
+    pub fn expand_args(
+        &self,
+        args: Vec<OsString>,
+        command: &Command,
+    ) -> Result<Vec<OsString>, String> {
+        let (mut expanded, mut args) = (args[..1].to_vec(), args[1..].to_vec());
+
+        // The options are given to the subcommand, if any
+        let mut command = command;
+        if let Some(subcommand) = args
+            .first()
+            .and_then(|arg| arg.to_str())
+            .and_then(|name| command.find_subcommand(name))
+        {
+            expanded.push(args.remove(0));
+            command = subcommand;
+        }
+
+        let mut options = Table::new();
+        let mut query_options = Table::new();
+        let mut arguments = Vec::new();
+        for arg in args {
+            match arg.to_str().and_then(|arg| arg.strip_prefix('@')) {
+                Some(name) => match self.queries.get(name) {
+                    Some(query) => query_options.extend(query.clone()),
+                    None => return Err(format!("Unknown query \"@{name}\"")),
+                },
+                None => arguments.push(arg),
+            }
+        }
+        // The defaults apply to the commands having the option, eg. not the extensions to info
+        options.extend(
+            self.defaults
+                .iter()
+                .filter(|(key, _)| long_arg(key, command).is_some())
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        options.extend(query_options);
+
+        for (key, value) in options {
+            if !is_given(&arguments, &key, command) {
+                expanded.extend(option_args(&key, &value, command)?);
+            }
+        }
+        expanded.extend(arguments);
+        Ok(expanded)
+    }
+}
+
+/// Path of the user configuration, `~/.config/lg/config.toml` on Linux.
+pub fn user_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("lg").join("config.toml"))
+}
+
+/// Path of the nearest project configuration, from the current directory up.
+pub fn project_config_path() -> Option<PathBuf> {
+    let current_dir = std::env::current_dir().ok()?;
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG))
+        .find(|path| path.is_file())
+}
+
+/// Returns the option of the command with the long name `key`.
+fn long_arg<'a>(key: &str, command: &'a Command) -> Option<&'a Arg> {
+    command
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some(key))
+}
+
+/// Tells if an option reads the next argument as its value, when it is not given with "=".
+fn takes_next_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+        && !arg.is_require_equals_set()
+        && arg
+            .get_num_args()
+            .is_none_or(|range| range.min_values() > 0)
+}
+
+/// Tells if the option is on the command line, by its long or short name.
+///
+/// The values of the options (eg. "-30d" in "--date -30d") are not read as short flags.
+fn is_given(arguments: &[OsString], key: &str, command: &Command) -> bool {
+    let short = long_arg(key, command).and_then(|arg| arg.get_short());
+    let mut value_expected = false;
+    for arg in arguments.iter().filter_map(|arg| arg.to_str()) {
+        if std::mem::take(&mut value_expected) {
+            continue;
+        }
+        if arg == "--" {
+            return false;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            let name = long.split('=').next().unwrap_or(long);
+            if name == key {
+                return true;
+            }
+            value_expected =
+                !long.contains('=') && long_arg(name, command).is_some_and(takes_next_value);
+        } else if let Some(shorts) = arg.strip_prefix('-') {
+            // A cluster of flags, the first option taking a value reading the rest of it
+            for (index, flag) in shorts.char_indices() {
+                if Some(flag) == short {
+                    return true;
+                }
+                let option = command
+                    .get_arguments()
+                    .find(|arg| arg.get_short() == Some(flag));
+                if option.is_some_and(|option| option.get_action().takes_values()) {
+                    value_expected = index + flag.len_utf8() == shorts.len()
+                        && option.is_some_and(takes_next_value);
+                    break;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Converts an option of the configuration to command line arguments.
+fn option_args(key: &str, value: &Value, command: &Command) -> Result<Vec<OsString>, String> {
+    let arg = long_arg(key, command).ok_or(format!("Unknown option \"{key}\" in configuration"))?;
+
+    let scalar = |value: &Value| match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        _ => Err(format!("Invalid value for \"{key}\" in configuration")),
+    };
+
+    let values = match (value, arg.get_action()) {
+        (Value::Boolean(true), ArgAction::SetTrue) => return Ok(vec![format!("--{key}").into()]),
+        (Value::Boolean(false), ArgAction::SetTrue) => return Ok(Vec::new()),
+        (Value::Array(values), _) => values.iter().map(scalar).collect::<Result<Vec<_>, _>>()?,
+        (value, _) => vec![scalar(value)?],
+    };
+    let values = match arg.get_value_delimiter() {
+        Some(delimiter) => vec![values.join(&delimiter.to_string())],
+        None => values,
+    };
+    Ok(values
+        .iter()
+        .map(|value| format!("--{key}={value}").into())
+        .collect())
+}
+
+#[test]
+fn test_expand_args() {
+    let config: Config = toml::from_str(
+        r#"
+        [defaults]
+        extensions = ["laz"]
+        recursive = true
+
+        [queries.recent]
+        date = ">=2023"
+        points-number = ">1k"
+        recursive = false
+        "#,
+    )
+    .unwrap();
+    let command = Command::new("lg")
+        .arg(clap::Arg::new("input"))
+        .arg(
+            clap::Arg::new("extensions")
+                .long("extensions")
+                .value_delimiter(','),
+        )
+        .arg(clap::Arg::new("date").long("date"))
+        .arg(clap::Arg::new("points_number").long("points-number"))
+        .arg(
+            clap::Arg::new("recursive")
+                .short('R')
+                .long("recursive")
+                .action(ArgAction::SetTrue),
+        )
+        .subcommand(Command::new("info").arg(clap::Arg::new("files")));
+    let expand = |args: &[&str]| {
+        config
+            .expand_args(args.iter().map(OsString::from).collect(), &command)
+            .map(|args| args.join(&OsString::from(" ")).into_string().unwrap())
+    };
+
+    assert_eq!(
+        expand(&["lg", "dir"]),
+        Ok(String::from("lg --extensions=laz --recursive dir"))
+    );
+    assert_eq!(
+        expand(&["lg", "@recent", "--date=2022", "dir"]),
+        Ok(String::from(
+            "lg --extensions=laz --points-number=>1k --date=2022 dir"
+        ))
+    );
+    assert!(expand(&["lg", "@unknown", "dir"]).is_err());
+    // The values of the options are not short flags
+    assert_eq!(
+        expand(&["lg", "--date", "-R", "dir"]),
+        Ok(String::from(
+            "lg --extensions=laz --recursive --date -R dir"
+        ))
+    );
+    assert_eq!(
+        expand(&["lg", "-R", "dir"]),
+        Ok(String::from("lg --extensions=laz -R dir"))
+    );
+    // The defaults the subcommand doesn't have are skipped, not the options of the queries
+    assert_eq!(
+        expand(&["lg", "info", "file.las"]),
+        Ok(String::from("lg info file.las"))
+    );
+    assert!(expand(&["lg", "info", "@recent", "file.las"]).is_err());
+}
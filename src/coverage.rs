@@ -0,0 +1,133 @@
+use std::cell::OnceCell;
+
+use geo::{Area, BooleanOps, Geometry, MultiPolygon};
+use serde_json::{json, Value};
+use wkt::ToWkt;
+
+use crate::footprint::FootprintSource;
+use crate::geometry::{polygons, to_geojson, union_all};
+use crate::header::MyHeader;
+
+/// Coverage of a reference area by the footprints of the selected files, for `lg coverage`.
+pub struct Coverage {
+    pub area: MultiPolygon,
+    /// Footprint of the files unioned over the area
+    pub footprint: FootprintSource,
+    /// Footprints of the files, empty when unknown or without area
+    pub footprints: Vec<MultiPolygon>,
+    /// Covered part of the area and gaps, computed once all the files are added
+    computed: OnceCell<(MultiPolygon, MultiPolygon)>,
+}
+
+impl Coverage {
+    pub fn new(area: &Geometry, footprint: FootprintSource) -> Result<Coverage, String> {
+        let area = union_all(
+            polygons(area)?
+                .into_iter()
+                .map(|polygon| MultiPolygon::new(vec![polygon]))
+                .collect(),
+        );
+        match area.unsigned_area() > 0.0 {
+            true => Ok(Coverage {
+                area,
+                footprint,
+                footprints: Vec::new(),
+                computed: OnceCell::new(),
+            }),
+            false => Err(String::from("the reference area is empty")),
+        }
+    }
+
+    pub fn add(&mut self, header: &MyHeader) {
+        let polygons = header
+            .footprint(self.footprint)
+            .and_then(|footprint| polygons(&footprint).ok());
+        self.footprints
+            .push(MultiPolygon::new(polygons.unwrap_or_default()));
+    }
+
+    /// Returns the covered part of the area and the gaps.
+    pub fn compute(&self) -> &(MultiPolygon, MultiPolygon) {
+        self.computed.get_or_init(|| {
+            let footprints = union_all(self.footprints.clone());
+            (
+                self.area.intersection(&footprints),
+                self.area.difference(&footprints),
+            )
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let (covered, gaps) = self.compute();
+        let area = self.area.unsigned_area();
+        [
+            format!("Files:           {}", self.footprints.len()),
+            format!("Area:            {area}"),
+            format!(
+                "Covered:         {} ({:.2}%)",
+                covered.unsigned_area(),
+                100.0 * covered.unsigned_area() / area
+            ),
+            format!("Gaps:            {}", gaps.0.len()),
+            gaps.wkt_string(),
+        ]
+        .join("\n")
+    }
+
+    pub fn to_json(&self) -> Value {
+        let (covered, gaps) = self.compute();
+        let area = self.area.unsigned_area();
+        json!({
+            "files": self.footprints.len(),
+            "area": area,
+            "covered_area": covered.unsigned_area(),
+            "covered_percent": 100.0 * covered.unsigned_area() / area,
+            "gaps": to_geojson(&Geometry::MultiPolygon(gaps.clone())),
+        })
+    }
+
+    /// Gaps as a GeoJSON feature collection, one feature by gap.
+    pub fn to_geojson(&self) -> Value {
+        let (_, gaps) = self.compute();
+        json!({
+            "type": "FeatureCollection",
+            "features": gaps.0.iter().map(|gap| json!({
+                "type": "Feature",
+                "properties": {"area": gap.unsigned_area()},
+                "geometry": to_geojson(&Geometry::Polygon(gap.clone())),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[test]
+fn test_coverage() {
+    let area: Geometry = geo::Rect::new((0.0, 0.0), (100.0, 100.0)).into();
+    let header = |min_x: f64| {
+        let mut builder = las::Builder::default();
+        builder.generating_software = String::from("lg");
+        let mut header = MyHeader::new(&builder.into_header().unwrap());
+        header.bbox = geo::Rect::new((min_x, 0.0), (min_x + 50.0, 80.0)).to_polygon();
+        header
+    };
+    let mut coverage = Coverage::new(&area, FootprintSource::Bbox).unwrap();
+    coverage.add(&header(0.0));
+    coverage.add(&header(50.0));
+
+    let (covered, gaps) = coverage.compute();
+    assert_eq!(covered.unsigned_area(), 8000.0);
+    assert_eq!(gaps.unsigned_area(), 2000.0);
+
+    // Files without sidecar cover nothing
+    let mut coverage = Coverage::new(&area, FootprintSource::Sidecar).unwrap();
+    let mut with_sidecar = header(0.0);
+    with_sidecar.sidecar =
+        Some(geo::Triangle::from([(0.0, 0.0), (100.0, 0.0), (0.0, 100.0)]).into());
+    coverage.add(&with_sidecar);
+    coverage.add(&header(50.0));
+    assert_eq!(coverage.footprints.len(), 2);
+    assert_eq!(coverage.compute().0.unsigned_area(), 5000.0);
+
+    let point = Geometry::Point(geo::Point::new(0.0, 0.0));
+    assert!(Coverage::new(&point, FootprintSource::Bbox).is_err());
+}
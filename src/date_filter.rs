@@ -1,61 +1,322 @@
 use crate::filter::Compare;
 use crate::header::MyHeader;
-use crate::list_filter::ListFilter;
-use crate::numeric_filter::NumericFilter;
-use chrono::NaiveDate;
-use log::{error, log, warn};
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
 use regex::Regex;
+use std::fmt::{Display, Formatter};
+
+/// Inclusive interval of days designated by a date expression (a single day, a whole year...).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DateInterval {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateInterval {
+    fn day(date: NaiveDate) -> DateInterval {
+        DateInterval {
+            start: date,
+            end: date,
+        }
+    }
+}
+
+/// Date expression of a condition, the relative ones being resolved on the day of the comparison.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DateValue {
+    /// Number of days, weeks, months or years from today ("-30d", "+2w", "today" being 0 days)
+    Relative {
+        count: i64,
+        unit: char,
+    },
+    Absolute(DateInterval),
+}
+
+impl DateValue {
+    /// Returns the interval designated on `today`, `None` when out of range.
+    pub fn resolve(&self, today: NaiveDate) -> Option<DateInterval> {
+        match *self {
+            DateValue::Absolute(interval) => Some(interval),
+            DateValue::Relative { count, unit } => {
+                let (forward, count) = (count >= 0, count.unsigned_abs());
+                let date = match unit {
+                    'd' | 'w' => {
+                        let days = Days::new(count.checked_mul(if unit == 'w' { 7 } else { 1 })?);
+                        match forward {
+                            true => today.checked_add_days(days),
+                            false => today.checked_sub_days(days),
+                        }
+                    }
+                    _ => {
+                        let months = u32::try_from(count).ok()?;
+                        let months =
+                            Months::new(months.checked_mul(if unit == 'y' { 12 } else { 1 })?);
+                        match forward {
+                            true => today.checked_add_months(months),
+                            false => today.checked_sub_months(months),
+                        }
+                    }
+                };
+                date.map(DateInterval::day)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DateCondition {
+    /// Comparison with a date expression (eg. ">=2023-01-01", "=2022", ">-30d")
+    Compare { operator: String, value: DateValue },
+    /// Inclusive range, each bound being optional (eg. "2023-01-01..2023-06-30", "2021..")
+    Range {
+        start: Option<DateValue>,
+        end: Option<DateValue>,
+    },
+}
 
-//use crate::filter::Compare;
-//use crate::header::MyHeader;
 #[derive(Debug)]
 pub struct DateFilter {
-    value: NaiveDate,
-    operator: String,
+    condition: DateCondition,
     header_attribute: String,
 }
 
-impl DateFilter {
-    pub fn new(input_string: &String, date_format: &String, attribute_name: &String) -> DateFilter {
-        let re: Regex =
-            Regex::new(r"(?P<operator>=|<=|>=|<|>|!=)(?P<date>.*)").unwrap();
+/// Parses a date expression, the relative ones being checked against `today`.
+///
+/// Are accepted, in this order : relative dates ("-30d", "+2w", "-6m", "-1y", "today"), dates
+/// in `date_format`, day-of-year dates ("2023-152") and years ("2023").
+pub fn parse_date(input: &str, date_format: &str, today: NaiveDate) -> Result<DateValue, String> {
+    let input = input.trim();
+    let relative_re = Regex::new(r"^(?P<sign>[+-])(?P<count>\d+)(?P<unit>[dwmy])$").unwrap();
+
+    if input == "today" {
+        return Ok(DateValue::Relative {
+            count: 0,
+            unit: 'd',
+        });
+    }
 
-        let groups = match re.captures(input_string) {
-            Some(groups) => groups,
-            None => panic!("Syntax error for --date parameter"),
+    if let Some(groups) = relative_re.captures(input) {
+        let count: i64 = groups["count"]
+            .parse()
+            .map_err(|_| format!("invalid relative date \"{input}\""))?;
+        let value = DateValue::Relative {
+            count: if &groups["sign"] == "-" {
+                -count
+            } else {
+                count
+            },
+            unit: groups["unit"].chars().next().unwrap(),
+        };
+        return match value.resolve(today) {
+            Some(_) => Ok(value),
+            None => Err(format!("relative date \"{input}\" out of range")),
+        };
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(input, date_format) {
+        return Ok(DateValue::Absolute(DateInterval::day(date)));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%j") {
+        return Ok(DateValue::Absolute(DateInterval::day(date)));
+    }
+
+    if input.len() == 4 && input.chars().all(|c| c.is_ascii_digit()) {
+        let year: i32 = input.parse().unwrap();
+        return Ok(DateValue::Absolute(DateInterval {
+            start: NaiveDate::from_ymd_opt(year, 1, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(year, 12, 31).unwrap(),
+        }));
+    }
+
+    Err(format!(
+        "unable to parse the date \"{input}\" (expected \"{date_format}\", a day of year, \
+        a year or a relative date)"
+    ))
+}
+
+/// Parses a date condition: "<op><date>", "<date>" or "<start>..<end>".
+pub fn parse_condition(
+    input: &str,
+    date_format: &str,
+    today: NaiveDate,
+) -> Result<DateCondition, String> {
+    let input = input.trim();
+
+    if let Some((start, end)) = input.split_once("..") {
+        let parse_bound = |bound: &str| match bound.trim() {
+            "" => Ok(None),
+            bound => parse_date(bound, date_format, today).map(Some),
         };
+        let (start, end) = (parse_bound(start)?, parse_bound(end)?);
+        let resolve = |bound: Option<DateValue>| bound.and_then(|bound| bound.resolve(today));
+        return match (resolve(start), resolve(end)) {
+            _ if start.is_none() && end.is_none() => {
+                Err(String::from("a range needs at least one bound"))
+            }
+            (Some(first), Some(last)) if first.start > last.end => Err(format!(
+                "empty range, {} is after {}",
+                first.start, last.end
+            )),
+            _ => Ok(DateCondition::Range { start, end }),
+        };
+    }
 
-        let operator = groups.name("operator").unwrap().as_str().to_string();
-        let date_string = groups.name("date").unwrap().as_str().to_string();
-        let parsed_date =
-            NaiveDate::parse_from_str(date_string.as_str(), date_format.as_str())
-                .expect("Unable to parse the date with the current formatter");
+    let operator = ["<=", ">=", "!=", "=", "<", ">"]
+        .into_iter()
+        .find(|operator| input.starts_with(operator))
+        .unwrap_or("=");
+    let value = parse_date(
+        input.strip_prefix(operator).unwrap_or(input),
+        date_format,
+        today,
+    )?;
 
-        DateFilter {
-            value: parsed_date,
-            operator,
+    Ok(DateCondition::Compare {
+        operator: operator.to_string(),
+        value,
+    })
+}
+
+impl DateCondition {
+    /// Compares a date, the relative dates of the condition being resolved on `today`.
+    pub fn matches(&self, date: NaiveDate, today: NaiveDate) -> bool {
+        match self {
+            DateCondition::Compare { operator, value } => {
+                let Some(value) = value.resolve(today) else {
+                    return false;
+                };
+                match operator.as_str() {
+                    "=" => value.start <= date && date <= value.end,
+                    "!=" => date < value.start || value.end < date,
+                    ">" => value.end < date,
+                    "<" => date < value.start,
+                    "<=" => date <= value.end,
+                    ">=" => value.start <= date,
+                    _ => panic!("Operator {} not found", operator),
+                }
+            }
+            DateCondition::Range { start, end } => {
+                let resolve = |bound: &Option<DateValue>| bound.map(|bound| bound.resolve(today));
+                let (start, end) = (resolve(start), resolve(end));
+                start.is_none_or(|start| start.is_some_and(|start| start.start <= date))
+                    && end.is_none_or(|end| end.is_some_and(|end| date <= end.end))
+            }
+        }
+    }
+}
+
+impl DateFilter {
+    pub fn new(
+        input_string: &str,
+        date_format: &str,
+        attribute_name: &str,
+    ) -> Result<DateFilter, String> {
+        let today = Local::now().date_naive();
+        Ok(DateFilter {
+            condition: parse_condition(input_string, date_format, today)?,
             header_attribute: attribute_name.to_string(),
+        })
+    }
+}
+
+/// Formats the interval as a year when it spans a whole year, as an ISO-8601 date otherwise.
+impl Display for DateInterval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let whole_year = self.start.year() == self.end.year()
+            && self.start.ordinal() == 1
+            && self.end.succ_opt().is_none_or(|next| next.ordinal() == 1);
+        match (self.start == self.end, whole_year) {
+            (_, true) => write!(f, "{}", self.start.year()),
+            (true, _) => write!(f, "{}", self.start.format("%Y-%m-%d")),
+            (false, false) => write!(
+                f,
+                "{}..{}",
+                self.start.format("%Y-%m-%d"),
+                self.end.format("%Y-%m-%d")
+            ),
         }
     }
 }
 
+/// Formats the relative dates as written, to keep their meaning when the query is parsed again.
+impl Display for DateValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DateValue::Relative { count: 0, .. } => write!(f, "today"),
+            DateValue::Relative { count, unit } => write!(f, "{count:+}{unit}"),
+            DateValue::Absolute(interval) => write!(f, "{interval}"),
+        }
+    }
+}
+
+impl Display for DateCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DateCondition::Compare { operator, value } => write!(f, "{operator}{value}"),
+            DateCondition::Range { start, end } => {
+                let bound = |bound: &Option<DateValue>| {
+                    bound.map_or(String::new(), |value| value.to_string())
+                };
+                write!(f, "{}..{}", bound(start), bound(end))
+            }
+        }
+    }
+}
+
+impl Display for DateFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.header_attribute, self.condition)
+    }
+}
+
 impl Compare for DateFilter {
+    fn attribute(&self) -> &str {
+        &self.header_attribute
+    }
+
+    fn header_value(&self, value: &MyHeader) -> String {
+        match self.header_attribute.as_str() {
+            "date" => value
+                .date
+                .map_or(String::from("-"), |date| date.to_string()),
+            _ => String::from("-"),
+        }
+    }
+
     fn compare(&self, value: &MyHeader) -> bool {
-        let operator = self.operator.as_str();
-        match value.date {
-            None => {
-                warn!("No date in header: Filter ignored");
-                true
-            }
-            Some(date) => match operator {
-                "=" => self.value == date,
-                "!=" => self.value != date,
-                ">" => self.value < date,
-                "<" => self.value > date,
-                "<=" => self.value >= date,
-                ">=" => self.value <= date,
-                _ => panic!("Operator {} not found", operator),
-            },
+        let header_date = match self.header_attribute.as_str() {
+            "date" => value.date,
+            _ => None,
+        };
+        match header_date {
+            None => false,
+            Some(date) => self.condition.matches(date, Local::now().date_naive()),
         }
     }
 }
+
+#[test]
+fn test_date_grammar() {
+    let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+    let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    let parse = |input| parse_condition(input, "%Y-%m-%d", today).unwrap();
+
+    assert!(parse("2023-01-01..2023-06-30").matches(day(2023, 6, 30), today));
+    assert!(!parse("2023-01-01..2023-06-30").matches(day(2023, 7, 1), today));
+    assert!(parse(">-30d").matches(day(2024, 3, 1), today));
+    assert!(!parse(">-30d").matches(day(2024, 2, 1), today));
+    assert!(parse("=2023").matches(day(2023, 12, 31), today));
+    assert!(!parse(">2023").matches(day(2023, 12, 31), today));
+    assert!(parse(">=2023").matches(day(2023, 1, 1), today));
+    assert!(parse("2023-152").matches(day(2023, 6, 1), today));
+    assert!(parse("2022..").matches(day(2024, 1, 1), today));
+    assert!(parse_condition("2023-13-01", "%Y-%m-%d", today).is_err());
+    assert!(parse_condition("2024..2023", "%Y-%m-%d", today).is_err());
+
+    // Relative dates are written back as is, and resolved on the day of the comparison
+    assert_eq!(parse(">-30d").to_string(), ">-30d");
+    assert_eq!(parse("today..").to_string(), "today..");
+    assert_eq!(parse("<=2023-06-30").to_string(), "<=2023-06-30");
+    let next_month = NaiveDate::from_ymd_opt(2024, 4, 15).unwrap();
+    assert!(!parse(">-30d").matches(day(2024, 3, 1), next_month));
+}
@@ -0,0 +1,280 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+
+use crate::header::MyHeader;
+use crate::walk::MatchedFile;
+
+/// Header attributes left out of the comparison (the VLRs are compared by their number).
+const IGNORED_FIELDS: [&str; 1] = ["vlrs"];
+
+/// Key pairing the files of the two deliveries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PairBy {
+    /// Path relative to the input folder
+    Name,
+    /// GUID of the header, files with a nil GUID being paired by name
+    Guid,
+}
+
+impl FromStr for PairBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(PairBy::Name),
+            "guid" => Ok(PairBy::Guid),
+            _ => Err(format!("Unknown pairing \"{s}\"")),
+        }
+    }
+}
+
+/// A header attribute whose value differs between the two files.
+#[derive(Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// A tile of both deliveries whose header changed, or which was renamed (paired by GUID).
+#[derive(Debug)]
+pub struct ModifiedTile {
+    pub old: PathBuf,
+    pub new: PathBuf,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Differences between the headers of two deliveries.
+#[derive(Debug, Default)]
+pub struct HeaderDiff {
+    pub added: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    pub modified: Vec<ModifiedTile>,
+    pub unchanged: usize,
+    /// Files left out of the comparison, their key being the one of a previous file of their
+    /// delivery (eg. a duplicated GUID)
+    pub collisions: Vec<PathBuf>,
+}
+
+/// Returns the attributes of the headers which differ, in the order of [`MyHeader::to_json`].
+pub fn diff_headers(old: &MyHeader, new: &MyHeader) -> Vec<FieldChange> {
+    let (old, new) = (old.to_json(), new.to_json());
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return Vec::new();
+    };
+
+    old.iter()
+        .filter(|(field, _)| !IGNORED_FIELDS.contains(&field.as_str()))
+        .filter_map(|(field, old_value)| {
+            let new_value = new.get(field).unwrap_or(&Value::Null);
+            (old_value != new_value).then(|| FieldChange {
+                field: field.clone(),
+                old: old_value.clone(),
+                new: new_value.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Path of a file relative to its input folder.
+fn relative(file: &MatchedFile) -> &Path {
+    file.path.strip_prefix(&file.root).unwrap_or(&file.path)
+}
+
+/// Key of a file for the pairing.
+fn pair_key(file: &MatchedFile, by: PairBy) -> String {
+    match by {
+        PairBy::Guid if !file.header.guid.is_nil() => file.header.guid.to_string(),
+        _ => relative(file).display().to_string(),
+    }
+}
+
+/// Indexes files by their pairing key, keeping the first file of each key and returning the
+/// other ones apart.
+fn index(files: &[MatchedFile], by: PairBy) -> (BTreeMap<String, &MatchedFile>, Vec<PathBuf>) {
+    let (mut indexed, mut collisions) = (BTreeMap::new(), Vec::new());
+    for file in files {
+        match indexed.entry(pair_key(file, by)) {
+            Entry::Vacant(entry) => {
+                entry.insert(file);
+            }
+            Entry::Occupied(_) => collisions.push(file.path.clone()),
+        }
+    }
+    (indexed, collisions)
+}
+
+impl HeaderDiff {
+    /// Pairs the files of the two deliveries and compares their headers.
+    ///
+    /// When several files share a key, only the first one is compared, the other ones being
+    /// reported as collisions.
+    pub fn new(old: &[MatchedFile], new: &[MatchedFile], by: PairBy) -> HeaderDiff {
+        let ((old, old_collisions), (mut new, new_collisions)) = (index(old, by), index(new, by));
+
+        let mut diff = HeaderDiff {
+            collisions: [old_collisions, new_collisions].concat(),
+            ..Default::default()
+        };
+        for (key, old_file) in old {
+            match new.remove(&key) {
+                Some(new_file) => {
+                    let changes = diff_headers(&old_file.header, &new_file.header);
+                    match changes.is_empty() && relative(old_file) == relative(new_file) {
+                        true => diff.unchanged += 1,
+                        false => diff.modified.push(ModifiedTile {
+                            old: old_file.path.clone(),
+                            new: new_file.path.clone(),
+                            changes,
+                        }),
+                    }
+                }
+                None => diff.removed.push(old_file.path.clone()),
+            }
+        }
+        diff.added = new.into_values().map(|file| file.path.clone()).collect();
+        diff
+    }
+
+    /// Keeps only the `limit` first differences, in the order added, removed, modified, collisions.
+    pub fn truncate(&mut self, limit: usize) {
+        self.added.truncate(limit);
+        self.removed.truncate(limit - self.added.len());
+        self.modified
+            .truncate(limit - self.added.len() - self.removed.len());
+        self.collisions
+            .truncate(limit - self.added.len() - self.removed.len() - self.modified.len());
+    }
+
+    /// Returns true if the deliveries are identical, without collision.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.collisions.is_empty()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        for path in &self.added {
+            lines.push(format!("+ {}", path.display()));
+        }
+        for path in &self.removed {
+            lines.push(format!("- {}", path.display()));
+        }
+        for tile in &self.modified {
+            lines.push(format!(
+                "~ {} -> {}",
+                tile.old.display(),
+                tile.new.display()
+            ));
+            for change in &tile.changes {
+                lines.push(format!(
+                    "    {}: {} -> {}",
+                    change.field, change.old, change.new
+                ));
+            }
+        }
+        for path in &self.collisions {
+            lines.push(format!("! {} (same key as another file)", path.display()));
+        }
+        lines.push(format!(
+            "{} added, {} removed, {} modified, {} unchanged, {} not compared",
+            self.added.len(),
+            self.removed.len(),
+            self.modified.len(),
+            self.unchanged,
+            self.collisions.len()
+        ));
+        lines.join("\n")
+    }
+
+    pub fn to_json(&self) -> Value {
+        let paths = |paths: &[PathBuf]| {
+            paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+        };
+        json!({
+            "added": paths(&self.added),
+            "removed": paths(&self.removed),
+            "modified": self.modified.iter().map(|tile| json!({
+                "old": tile.old.display().to_string(),
+                "new": tile.new.display().to_string(),
+                "changes": tile.changes.iter().map(|change| json!({
+                    "field": change.field,
+                    "old": change.old,
+                    "new": change.new,
+                })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+            "unchanged": self.unchanged,
+            "collisions": paths(&self.collisions),
+        })
+    }
+}
+
+#[test]
+fn test_header_diff() {
+    let file = |root: &str, name: &str, software: &str| {
+        let mut builder = las::Builder::default();
+        builder.generating_software = String::from(software);
+        MatchedFile {
+            input: 0,
+            root: PathBuf::from(root),
+            path: Path::new(root).join(name),
+            size: 0,
+            header: MyHeader::new(&builder.into_header().unwrap()),
+        }
+    };
+    let old = vec![
+        file("a", "1.las", "lg"),
+        file("a", "2.las", "lg"),
+        file("a", "3.las", "lg"),
+    ];
+    let new = vec![
+        file("b", "2.las", "lg"),
+        file("b", "3.las", "PDAL"),
+        file("b", "4.las", "lg"),
+    ];
+
+    let diff = HeaderDiff::new(&old, &new, PairBy::Name);
+    assert_eq!(diff.added, [PathBuf::from("b/4.las")]);
+    assert_eq!(diff.removed, [PathBuf::from("a/1.las")]);
+    assert_eq!(diff.unchanged, 1);
+    assert_eq!(
+        diff.modified[0].changes,
+        [FieldChange {
+            field: String::from("generating_software"),
+            old: json!("lg"),
+            new: json!("PDAL"),
+        }]
+    );
+
+    assert!(diff.collisions.is_empty());
+
+    // Files sharing a GUID in a delivery are reported, not silently dropped
+    let guid = uuid::Uuid::from_u128(42);
+    let with_guid = |root: &str, name: &str| {
+        let mut file = file(root, name, "lg");
+        file.header.guid = guid;
+        file
+    };
+    let guid_diff = HeaderDiff::new(
+        &[with_guid("a", "1.las"), with_guid("a", "2.las")],
+        &[with_guid("b", "1.las")],
+        PairBy::Guid,
+    );
+    assert_eq!(guid_diff.collisions, [PathBuf::from("a/2.las")]);
+    assert_eq!(guid_diff.unchanged, 1);
+    assert!(!guid_diff.is_empty());
+
+    let mut diff = diff;
+    diff.truncate(2);
+    assert_eq!((diff.added.len(), diff.removed.len()), (1, 1));
+    assert!(diff.modified.is_empty());
+}
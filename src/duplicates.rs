@@ -0,0 +1,245 @@
+use std::cell::OnceCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+use geo::{Area, BooleanOps, BoundingRect, MultiPolygon};
+use serde_json::{json, Value};
+
+use crate::footprint::FootprintSource;
+use crate::geometry::polygons;
+use crate::group::csv_field;
+use crate::header::MyHeader;
+use crate::numeric_filter::{parse_condition, NumericCondition};
+
+/// Reason two tiles are reported as duplicates, from the strongest to the weakest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DuplicateKind {
+    /// All the attributes of the headers are equal
+    Header,
+    Guid,
+    /// The footprints overlap beyond the --overlap threshold
+    Overlap,
+}
+
+impl Display for DuplicateKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DuplicateKind::Header => write!(f, "identical headers"),
+            DuplicateKind::Guid => write!(f, "same guid"),
+            DuplicateKind::Overlap => write!(f, "overlap"),
+        }
+    }
+}
+
+/// Parses an overlap threshold, a numeric condition on percents (eg. ">10%", "5..50%").
+pub fn parse_overlap(input: &str) -> Result<NumericCondition, String> {
+    parse_condition(&input.replace('%', ""))
+}
+
+/// Area of the intersection of the footprints, in percent of the smallest one.
+pub fn overlap_ratio(first: &MultiPolygon, second: &MultiPolygon) -> f64 {
+    let smallest = first.unsigned_area().min(second.unsigned_area());
+    match smallest.is_finite() && smallest > 0.0 {
+        true => 100.0 * first.intersection(second).unsigned_area() / smallest,
+        false => 0.0,
+    }
+}
+
+/// Two duplicated files (as indexes of [`Duplicates::files`]), with their strongest reason and
+/// the overlap of their footprints.
+pub type Pair = (usize, usize, DuplicateKind, f64);
+
+/// Tiles of a mosaic sharing a GUID, a header or a part of their footprint, for --duplicates.
+#[derive(Default)]
+pub struct Duplicates {
+    /// Threshold of the overlap check, which is skipped without one
+    pub overlap: Option<NumericCondition>,
+    /// Footprint of the files compared by the overlap check
+    pub footprint: FootprintSource,
+    pub files: Vec<(PathBuf, MyHeader)>,
+    /// Pairs found once all the files are added
+    pairs: OnceCell<Vec<Pair>>,
+}
+
+impl Duplicates {
+    pub fn new(overlap: Option<NumericCondition>, footprint: FootprintSource) -> Duplicates {
+        Duplicates {
+            overlap,
+            footprint,
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, path: PathBuf, header: MyHeader) {
+        self.files.push((path, header));
+    }
+
+    /// Returns the pairs of duplicated files, computed on the first call once all the files are
+    /// added.
+    pub fn pairs(&self) -> &[Pair] {
+        self.pairs.get_or_init(|| self.find_pairs())
+    }
+
+    fn find_pairs(&self) -> Vec<Pair> {
+        // Footprints without area (or unknown, like a hull without points) never overlap
+        let footprints = self
+            .files
+            .iter()
+            .map(|(_, header)| {
+                let footprint = header.footprint(self.footprint)?;
+                polygons(&footprint).ok().map(MultiPolygon::new)
+            })
+            .collect::<Vec<_>>();
+        let overlap_of = |i: usize, j: usize| match (&footprints[i], &footprints[j]) {
+            (Some(first), Some(second)) => overlap_ratio(first, second),
+            _ => 0.0,
+        };
+
+        let mut pairs = BTreeMap::new();
+        let mut add_groups = |groups: HashMap<String, Vec<usize>>, kind| {
+            for group in groups.values() {
+                for (n, &i) in group.iter().enumerate() {
+                    for &j in &group[n + 1..] {
+                        pairs.entry((i, j)).or_insert(kind);
+                    }
+                }
+            }
+        };
+
+        let mut headers: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut guids: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, (_, header)) in self.files.iter().enumerate() {
+            headers
+                .entry(header.to_json().to_string())
+                .or_default()
+                .push(i);
+            if !header.guid.is_nil() {
+                guids.entry(header.guid.to_string()).or_default().push(i);
+            }
+        }
+        add_groups(headers, DuplicateKind::Header);
+        add_groups(guids, DuplicateKind::Guid);
+
+        if let Some(overlap) = &self.overlap {
+            // Sweep along x, the files further than the max x of a file can't overlap it
+            let mut boxes = footprints
+                .iter()
+                .enumerate()
+                .filter_map(|(i, footprint)| Some((i, footprint.as_ref()?.bounding_rect()?)))
+                .collect::<Vec<_>>();
+            boxes.sort_by(|(_, first), (_, second)| first.min().x.total_cmp(&second.min().x));
+            for (n, &(i, first)) in boxes.iter().enumerate() {
+                for &(j, second) in &boxes[n + 1..] {
+                    if second.min().x >= first.max().x {
+                        break;
+                    }
+                    let ratio = overlap_of(i, j);
+                    if ratio > 0.0 && overlap.matches(ratio) {
+                        pairs
+                            .entry((i.min(j), i.max(j)))
+                            .or_insert(DuplicateKind::Overlap);
+                    }
+                }
+            }
+        }
+
+        let mut pairs = pairs
+            .into_iter()
+            .map(|((i, j), kind)| (i, j, kind, overlap_of(i, j)))
+            .collect::<Vec<_>>();
+        pairs.sort_by_key(|&(i, j, kind, _)| (kind, i, j));
+        pairs
+    }
+
+    pub fn to_text(&self) -> String {
+        let pairs = self.pairs();
+        let mut lines = Vec::new();
+        for &(i, j, kind, overlap) in pairs {
+            lines.push(format!(
+                "{} {}: {kind} ({overlap:.1}% overlap)",
+                self.files[i].0.display(),
+                self.files[j].0.display(),
+            ));
+        }
+        lines.push(format!(
+            "{} duplicate pair(s) in {} file(s)",
+            pairs.len(),
+            self.files.len()
+        ));
+        lines.join("\n")
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut rows = vec![String::from("first,second,reason,overlap")];
+        for &(i, j, kind, overlap) in self.pairs() {
+            rows.push(format!(
+                "{},{},{kind},{overlap}",
+                csv_field(&self.files[i].0.display().to_string()),
+                csv_field(&self.files[j].0.display().to_string()),
+            ));
+        }
+        rows.join("\n")
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::Array(
+            self.pairs()
+                .iter()
+                .map(|&(i, j, kind, overlap)| {
+                    json!({
+                        "first": self.files[i].0.display().to_string(),
+                        "second": self.files[j].0.display().to_string(),
+                        "reason": kind.to_string(),
+                        "overlap": overlap,
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_duplicates() {
+    let header = |min_x: f64, software: &str| {
+        let mut builder = las::Builder::default();
+        builder.generating_software = String::from(software);
+        let mut header = MyHeader::new(&builder.into_header().unwrap());
+        header.bounds.min = las::Vector {
+            x: min_x,
+            y: 0.0,
+            z: 0.0,
+        };
+        header.bounds.max = las::Vector {
+            x: min_x + 100.0,
+            y: 100.0,
+            z: 0.0,
+        };
+        header.bbox = geo::Rect::new((min_x, 0.0), (min_x + 100.0, 100.0)).to_polygon();
+        header
+    };
+
+    let overlap = || Some(parse_overlap(">10%").unwrap());
+    let mut duplicates = Duplicates::new(overlap(), FootprintSource::Bbox);
+    duplicates.add(PathBuf::from("a.las"), header(0.0, "lg"));
+    duplicates.add(PathBuf::from("b.las"), header(0.0, "lg"));
+    duplicates.add(PathBuf::from("c.las"), header(50.0, "PDAL"));
+    duplicates.add(PathBuf::from("d.las"), header(95.0, "PDAL"));
+    duplicates.add(PathBuf::from("e.las"), header(1000.0, "PDAL"));
+
+    assert_eq!(
+        duplicates.pairs(),
+        [
+            (0, 1, DuplicateKind::Header, 100.0),
+            (0, 2, DuplicateKind::Overlap, 50.0),
+            (1, 2, DuplicateKind::Overlap, 50.0),
+            (2, 3, DuplicateKind::Overlap, 55.0),
+        ]
+    );
+
+    // The hull is unknown without the points, so no footprint overlaps
+    let mut duplicates = Duplicates::new(overlap(), FootprintSource::Hull);
+    duplicates.add(PathBuf::from("a.las"), header(0.0, "lg"));
+    duplicates.add(PathBuf::from("c.las"), header(50.0, "PDAL"));
+    assert!(duplicates.pairs().is_empty());
+}
@@ -1,18 +1,90 @@
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::ops::Index;
+use std::path::Path;
+use std::str::FromStr;
 
+use log::warn;
+
+use crate::bool_filter::BoolFilter;
 use crate::date_filter::DateFilter;
-use crate::header::MyHeader;
+use crate::header::{MyHeader, POINT_ATTRIBUTES};
 use crate::list_filter::ListFilter;
 use crate::numeric_filter::NumericFilter;
+use crate::query::parse_query;
 use crate::spatial_filter::SpatialFilter;
 use crate::string_filter::StringFilter;
+use crate::tile_name::NAME_PREFIX;
+use crate::walk::{read_header, WalkOptions};
 
 pub trait Compare {
+    /// Name of the header attribute the filter is applied on.
+    fn attribute(&self) -> &str;
+    /// Value of the attribute in the header, as displayed by --explain.
+    fn header_value(&self, value: &MyHeader) -> String;
     fn compare(&self, value: &MyHeader) -> bool;
 }
 
+/// Behavior of the filters when the header has no value for their attribute.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MissingPolicy {
+    /// The filter is ignored, the file passes it
+    #[default]
+    Match,
+    /// The file is rejected
+    Skip,
+    /// The file is rejected and reported as an error
+    Error,
+}
+
+impl FromStr for MissingPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "match" => Ok(MissingPolicy::Match),
+            "skip" => Ok(MissingPolicy::Skip),
+            "error" => Ok(MissingPolicy::Error),
+            _ => Err(format!("Unknown missing value policy \"{s}\"")),
+        }
+    }
+}
+
+/// Error raised when a filtered attribute is missing under [`MissingPolicy::Error`].
+#[derive(Debug, PartialEq)]
+pub struct MissingValueError {
+    pub attribute: String,
+}
+
+impl Display for MissingValueError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No {} in header", self.attribute)
+    }
+}
+
 impl Compare for Filter {
+    fn attribute(&self) -> &str {
+        match self {
+            Filter::FNumeric(filter) => filter.attribute(),
+            Filter::FSpatial(filter) => filter.attribute(),
+            Filter::FString(filter) => filter.attribute(),
+            Filter::FDate(filter) => filter.attribute(),
+            Filter::FList(filter) => filter.attribute(),
+            Filter::FBool(filter) => filter.attribute(),
+        }
+    }
+
+    fn header_value(&self, value: &MyHeader) -> String {
+        match self {
+            Filter::FNumeric(filter) => filter.header_value(value),
+            Filter::FSpatial(filter) => filter.header_value(value),
+            Filter::FString(filter) => filter.header_value(value),
+            Filter::FDate(filter) => filter.header_value(value),
+            Filter::FList(filter) => filter.header_value(value),
+            Filter::FBool(filter) => filter.header_value(value),
+        }
+    }
+
     fn compare(&self, value: &MyHeader) -> bool {
         match self {
             Filter::FNumeric(filter) => filter.compare(value),
@@ -20,17 +92,33 @@ impl Compare for Filter {
             Filter::FString(filter) => filter.compare(value),
             Filter::FDate(filter) => filter.compare(value),
             Filter::FList(filter) => filter.compare(value),
+            Filter::FBool(filter) => filter.compare(value),
+        }
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::FNumeric(filter) => filter.fmt(f),
+            Filter::FSpatial(filter) => filter.fmt(f),
+            Filter::FString(filter) => filter.fmt(f),
+            Filter::FDate(filter) => filter.fmt(f),
+            Filter::FList(filter) => filter.fmt(f),
+            Filter::FBool(filter) => filter.fmt(f),
         }
     }
 }
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Filter {
     FSpatial(SpatialFilter),
     FString(StringFilter),
     FNumeric(NumericFilter),
     FDate(DateFilter),
     FList(ListFilter),
+    FBool(BoolFilter),
 }
 
 pub struct LasHeaderFilterIterator<'a> {
@@ -53,6 +141,10 @@ pub struct LasHeaderFilter {
     pub transform_filter: Option<Filter>,
     pub geom_filter: Option<Filter>,
     pub number_of_points_filter: Option<Filter>,
+    pub attribute_filters: Vec<Filter>,
+    pub missing_policy: MissingPolicy,
+    /// Missing value policies overriding `missing_policy`, by attribute
+    pub missing_policy_overrides: HashMap<String, MissingPolicy>,
 }
 
 impl LasHeaderFilter {
@@ -70,19 +162,183 @@ impl LasHeaderFilter {
             transform_filter: None,
             geom_filter: None,
             number_of_points_filter: None,
+            attribute_filters: Vec::new(),
+            missing_policy: MissingPolicy::Match,
+            missing_policy_overrides: HashMap::new(),
+        }
+    }
+
+    /// Adds a filter in the slot of its attribute, or to `attribute_filters` if there is no
+    /// such slot or if it is already taken.
+    pub fn add(&mut self, filter: Filter) {
+        let slot = match filter.attribute() {
+            "number_of_points" => &mut self.number_of_points_filter,
+            "file_source_id" => &mut self.file_source_id_filter,
+            "gps_time_type" => &mut self.gps_time_type_filter,
+            "version" => &mut self.version_filter,
+            "guid" => &mut self.guid_filter,
+            "generating_software" => &mut self.generating_software_filter,
+            "system_identifier" => &mut self.system_identifier_filter,
+            "date" => &mut self.date_filter,
+            "point_format" => &mut self.point_format_filter,
+            "bbox" | "hull" | "sidecar" => &mut self.geom_filter,
+            _ => return self.attribute_filters.push(filter),
+        };
+        match slot {
+            None => *slot = Some(filter),
+            Some(_) => self.attribute_filters.push(filter),
+        }
+    }
+
+    /// Takes the filters out of their slots, in evaluation order.
+    pub fn into_filters(self) -> Vec<Filter> {
+        [
+            self.file_source_id_filter,
+            self.gps_time_type_filter,
+            self.synthetic_return_numbers_filter,
+            self.guid_filter,
+            self.version_filter,
+            self.system_identifier_filter,
+            self.generating_software_filter,
+            self.date_filter,
+            self.point_format_filter,
+            self.transform_filter,
+            self.geom_filter,
+            self.number_of_points_filter,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.attribute_filters)
+        .collect()
+    }
+
+    /// Returns true if a filter needs the points of the files to be scanned.
+    pub fn needs_points(&self) -> bool {
+        self.filters()
+            .any(|filter| POINT_ATTRIBUTES.contains(&filter.attribute()))
+    }
+
+    /// Returns true if a filter compares fields captured from the file names.
+    pub fn needs_names(&self) -> bool {
+        self.filters()
+            .any(|filter| filter.attribute().starts_with(NAME_PREFIX))
+    }
+
+    /// Returns true if a filter compares the sidecar footprints of the files.
+    pub fn needs_sidecars(&self) -> bool {
+        self.filters().any(|filter| filter.attribute() == "sidecar")
+    }
+
+    /// Returns true if a filter needs the headers to be validated.
+    pub fn needs_validation(&self) -> bool {
+        self.filters().any(|filter| filter.attribute() == "valid")
+    }
+
+    /// Returns the spatial filter, if any.
+    pub fn spatial_filter(&self) -> Option<&SpatialFilter> {
+        match &self.geom_filter {
+            Some(Filter::FSpatial(filter)) => Some(filter),
+            _ => None,
         }
     }
+
+    pub fn missing_policy(&self, attribute: &str) -> MissingPolicy {
+        *self
+            .missing_policy_overrides
+            .get(attribute)
+            .unwrap_or(&self.missing_policy)
+    }
+
+    /// Filters a header read by the las crate.
+    pub fn filter_las_header(&self, header: &las::Header) -> bool {
+        self.filter(&MyHeader::new(header))
+    }
+
+    /// Reads the header of a file and filters it.
+    pub fn filter_path(&self, path: &Path) -> Result<bool, String> {
+        let header = self.read_header(path)?;
+        self.try_filter(&header)
+            .map_err(|error| format!("{}: {error}", path.display()))
+    }
+
+    /// Reads the header of a file with the values the filters compare: all its points, its
+    /// sidecar footprint and its issues.
+    pub fn read_header(&self, path: &Path) -> Result<MyHeader, String> {
+        let options = WalkOptions {
+            scan_points: self.needs_points().then_some(1),
+            validate: self.needs_validation(),
+            sidecars: self.needs_sidecars(),
+            ..Default::default()
+        };
+        read_header(path, &options)
+    }
+
     pub fn filter(&self, header: &MyHeader) -> bool {
-        for i in &mut self.into_iter() {
-            match i {
-                Some(filter) => match filter.compare(header) {
-                    true => {}
-                    false => return false,
-                },
-                None => {}
+        self.try_filter(header).unwrap_or(false)
+    }
+
+    /// Same as [`LasHeaderFilter::filter`], but fails when a filtered attribute is missing
+    /// from the header and its policy is [`MissingPolicy::Error`].
+    pub fn try_filter(&self, header: &MyHeader) -> Result<bool, MissingValueError> {
+        for filter in self.filters() {
+            match self.verdict(filter, header) {
+                Verdict::Match => {}
+                Verdict::Missing(MissingPolicy::Match) => {
+                    warn!(
+                        "No {} in header: Filter \"{filter}\" ignored",
+                        filter.attribute()
+                    )
+                }
+                Verdict::NoMatch | Verdict::Missing(MissingPolicy::Skip) => return Ok(false),
+                Verdict::Missing(MissingPolicy::Error) => {
+                    return Err(MissingValueError {
+                        attribute: filter.attribute().to_string(),
+                    })
+                }
             }
         }
-        return true;
+        Ok(true)
+    }
+
+    /// Returns the verdict of every filter, without stopping at the first rejection.
+    pub fn evaluate(&self, header: &MyHeader) -> Vec<(&Filter, Verdict)> {
+        self.filters()
+            .map(|filter| (filter, self.verdict(filter, header)))
+            .collect()
+    }
+
+    fn filters(&self) -> impl Iterator<Item = &Filter> {
+        self.into_iter().flatten().chain(&self.attribute_filters)
+    }
+
+    fn verdict(&self, filter: &Filter, header: &MyHeader) -> Verdict {
+        let attribute = filter.attribute();
+        match (header.has_attribute(attribute), filter.compare(header)) {
+            (true, true) => Verdict::Match,
+            (true, false) => Verdict::NoMatch,
+            (false, _) => Verdict::Missing(self.missing_policy(attribute)),
+        }
+    }
+}
+
+/// Result of a filter applied on a header.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Verdict {
+    Match,
+    NoMatch,
+    /// The attribute is missing from the header, the policy decides the result
+    Missing(MissingPolicy),
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Match => write!(f, "match"),
+            Verdict::NoMatch => write!(f, "no match"),
+            Verdict::Missing(MissingPolicy::Match) => write!(f, "missing (ignored)"),
+            Verdict::Missing(MissingPolicy::Skip) => write!(f, "missing (rejected)"),
+            Verdict::Missing(MissingPolicy::Error) => write!(f, "missing (error)"),
+        }
     }
 }
 
@@ -135,8 +391,78 @@ impl<'a> Iterator for LasHeaderFilterIterator<'a> {
     }
 }
 
+/// Formats the filter as a canonical query, which [`LasHeaderFilter::from_str`] parses back.
 impl Display for LasHeaderFilter {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        todo!()
+        let clauses: Vec<String> = self.filters().map(|filter| filter.to_string()).collect();
+        write!(f, "{}", clauses.join(" and "))
     }
-}
\ No newline at end of file
+}
+
+impl FromStr for LasHeaderFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_query(s)
+    }
+}
+
+#[test]
+fn test_missing_policy() {
+    let mut builder = las::Builder::default();
+    builder.date = None;
+    let header = MyHeader::new(&builder.into_header().unwrap());
+
+    let mut filter = LasHeaderFilter::new();
+    filter.date_filter = Some(Filter::FDate(
+        DateFilter::new(">2020", "%Y-%m-%d", "date").unwrap(),
+    ));
+    assert_eq!(filter.try_filter(&header), Ok(true));
+
+    filter.missing_policy = MissingPolicy::Error;
+    assert!(filter.try_filter(&header).is_err());
+
+    filter
+        .missing_policy_overrides
+        .insert(String::from("date"), MissingPolicy::Skip);
+    assert_eq!(filter.try_filter(&header), Ok(false));
+}
+
+#[test]
+fn test_filter_path_reads_points() {
+    use las::Write;
+
+    let path = std::env::temp_dir().join(format!("lg-filter-path-{}.las", std::process::id()));
+    let mut writer = las::Writer::from_path(&path, las::Header::default()).unwrap();
+    writer
+        .write(las::Point {
+            classification: las::point::Classification::Ground,
+            ..Default::default()
+        })
+        .unwrap();
+    writer.close().unwrap();
+
+    let ground = "has_class in {2}".parse::<LasHeaderFilter>().unwrap();
+    let water = "has_class in {9}".parse::<LasHeaderFilter>().unwrap();
+    let (ground, water) = (ground.filter_path(&path), water.filter_path(&path));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(ground, Ok(true));
+    assert_eq!(water, Ok(false));
+}
+
+#[test]
+fn test_unmatched_names() {
+    let filter = "name.x 650..700".parse::<LasHeaderFilter>().unwrap();
+    let mut header = MyHeader::new(&las::Builder::default().into_header().unwrap());
+    assert!(filter.needs_names());
+
+    // Without pattern the fields are missing, and ignored by the default policy
+    assert_eq!(
+        filter.evaluate(&header)[0].1,
+        Verdict::Missing(MissingPolicy::Match)
+    );
+    // A name not matching the pattern has no field matching the condition
+    header.name_fields = Some(Default::default());
+    assert_eq!(filter.evaluate(&header)[0].1, Verdict::NoMatch);
+    assert!(!filter.filter(&header));
+}
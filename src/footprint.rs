@@ -0,0 +1,81 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use geo::Geometry;
+
+use crate::geometry::read_geometry_file;
+
+/// Extensions of the sidecar footprints, looked up in this order next to the tiles.
+pub const SIDECAR_EXTENSIONS: [&str; 3] = ["geojson", "json", "wkt"];
+
+/// Footprint of a file compared by the spatial filters.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FootprintSource {
+    /// Axis-aligned box of the header bounds
+    #[default]
+    Bbox,
+    /// Convex hull of the points read with --scan-points
+    Hull,
+    /// Polygon of a GeoJSON or WKT file next to the tile, with the same name
+    Sidecar,
+}
+
+impl FootprintSource {
+    /// Name of the footprint, the attribute of the spatial filters in queries.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FootprintSource::Bbox => "bbox",
+            FootprintSource::Hull => "hull",
+            FootprintSource::Sidecar => "sidecar",
+        }
+    }
+}
+
+impl Display for FootprintSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for FootprintSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bbox" => Ok(FootprintSource::Bbox),
+            "hull" => Ok(FootprintSource::Hull),
+            "sidecar" => Ok(FootprintSource::Sidecar),
+            _ => Err(format!("Unknown footprint \"{s}\"")),
+        }
+    }
+}
+
+/// Returns the sidecar footprint file of a tile, if there is one.
+pub fn sidecar_path(path: &Path) -> Option<PathBuf> {
+    SIDECAR_EXTENSIONS
+        .iter()
+        .map(|extension| path.with_extension(extension))
+        .find(|sidecar| sidecar.is_file())
+}
+
+/// Reads the sidecar footprint of a tile, `None` when it has none.
+pub fn read_sidecar(path: &Path) -> Result<Option<Geometry>, String> {
+    match sidecar_path(path) {
+        Some(sidecar) => read_geometry_file(&sidecar.to_string_lossy()).map(Some),
+        None => Ok(None),
+    }
+}
+
+#[test]
+fn test_sidecar() {
+    let folder = std::env::temp_dir().join(format!("lg-sidecar-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    let tile = folder.join("tile.laz");
+    assert_eq!(read_sidecar(&tile), Ok(None));
+
+    std::fs::write(folder.join("tile.wkt"), "POLYGON((0 0, 10 0, 10 5, 0 0))").unwrap();
+    let footprint = read_sidecar(&tile);
+    std::fs::remove_dir_all(&folder).unwrap();
+    assert!(matches!(footprint, Ok(Some(Geometry::Polygon(_)))));
+}
@@ -0,0 +1,338 @@
+use std::f64::consts::TAU;
+use std::path::Path;
+
+use geo::{
+    BooleanOps, Coord, Geometry, GeometryCollection, Line, LineString, LinesIter, MultiLineString,
+    MultiPolygon, Point, Polygon,
+};
+use serde_json::{json, Value};
+use wkt::TryFromWkt;
+
+/// Reads a geometry from a GeoJSON file (geometry, feature or feature collection) or a WKT file.
+pub fn read_geometry_file(path: &str) -> Result<Geometry, String> {
+    let content = std::fs::read_to_string(Path::new(path))
+        .map_err(|error| format!("Impossible to read {path}: {error}"))?;
+    let geometry = match content.trim_start().starts_with('{') {
+        true => serde_json::from_str::<Value>(&content)
+            .map_err(|error| error.to_string())
+            .and_then(|value| from_geojson(&value)),
+        false => Geometry::try_from_wkt_str(content.trim()).map_err(|error| error.to_string()),
+    };
+    geometry.map_err(|error| format!("Invalid geometry in {path}: {error}"))
+}
+
+fn coord(value: &Value) -> Result<Coord, String> {
+    match value.as_array().map(|xy| (xy.first(), xy.get(1))) {
+        Some((Some(x), Some(y))) => match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => Ok(Coord { x, y }),
+            _ => Err(format!("invalid position {value}")),
+        },
+        _ => Err(format!("invalid position {value}")),
+    }
+}
+
+fn array<'a>(value: &'a Value, what: &str) -> Result<&'a Vec<Value>, String> {
+    value
+        .as_array()
+        .ok_or_else(|| format!("{what} should be an array"))
+}
+
+fn line_string(value: &Value) -> Result<LineString, String> {
+    array(value, "coordinates")?
+        .iter()
+        .map(coord)
+        .collect::<Result<Vec<_>, _>>()
+        .map(LineString::new)
+}
+
+fn polygon(value: &Value) -> Result<Polygon, String> {
+    let mut rings = array(value, "coordinates")?
+        .iter()
+        .map(line_string)
+        .collect::<Result<Vec<_>, _>>()?;
+    match rings.is_empty() {
+        true => Err(String::from("a polygon needs an exterior ring")),
+        false => {
+            let exterior = rings.remove(0);
+            Ok(Polygon::new(exterior, rings))
+        }
+    }
+}
+
+/// Merges the geometries of a collection, as a multipolygon when they are all polygons.
+fn collect(mut geometries: Vec<Geometry>) -> Geometry {
+    if geometries.len() == 1 {
+        return geometries.remove(0);
+    }
+    let polygons = geometries
+        .iter()
+        .try_fold(Vec::new(), |mut polygons, geometry| {
+            match geometry {
+                Geometry::Polygon(polygon) => polygons.push(polygon.clone()),
+                Geometry::MultiPolygon(multi) => polygons.extend(multi.0.iter().cloned()),
+                _ => return None,
+            }
+            Some(polygons)
+        });
+    match polygons {
+        Some(polygons) => Geometry::MultiPolygon(MultiPolygon::new(polygons)),
+        None => Geometry::GeometryCollection(GeometryCollection::new_from(geometries)),
+    }
+}
+
+/// Converts a GeoJSON object to a geometry, the features being merged.
+pub fn from_geojson(value: &Value) -> Result<Geometry, String> {
+    let coordinates = || value.get("coordinates").unwrap_or(&Value::Null);
+    let many = |key: &str| {
+        array(value.get(key).unwrap_or(&Value::Null), key)?
+            .iter()
+            .map(from_geojson)
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    match value.get("type").and_then(Value::as_str) {
+        Some("Point") => coord(coordinates()).map(|c| Geometry::Point(Point::from(c))),
+        Some("MultiPoint") => array(coordinates(), "coordinates")?
+            .iter()
+            .map(|c| coord(c).map(Point::from))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|points| Geometry::MultiPoint(points.into())),
+        Some("LineString") => line_string(coordinates()).map(Geometry::LineString),
+        Some("MultiLineString") => array(coordinates(), "coordinates")?
+            .iter()
+            .map(line_string)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|lines| Geometry::MultiLineString(MultiLineString::new(lines))),
+        Some("Polygon") => polygon(coordinates()).map(Geometry::Polygon),
+        Some("MultiPolygon") => array(coordinates(), "coordinates")?
+            .iter()
+            .map(polygon)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|polygons| Geometry::MultiPolygon(MultiPolygon::new(polygons))),
+        Some("GeometryCollection") => many("geometries").map(collect),
+        Some("Feature") => from_geojson(value.get("geometry").unwrap_or(&Value::Null)),
+        Some("FeatureCollection") => many("features").map(collect),
+        Some(other) => Err(format!("unknown GeoJSON type \"{other}\"")),
+        None => Err(String::from("GeoJSON object without type")),
+    }
+}
+
+fn ring_coordinates(ring: &LineString) -> Value {
+    ring.coords().map(|c| json!([c.x, c.y])).collect()
+}
+
+fn polygon_coordinates(polygon: &Polygon) -> Value {
+    std::iter::once(polygon.exterior())
+        .chain(polygon.interiors())
+        .map(ring_coordinates)
+        .collect()
+}
+
+/// Converts a geometry to a GeoJSON geometry object.
+pub fn to_geojson(geometry: &Geometry) -> Value {
+    match geometry {
+        Geometry::Point(point) => json!({"type": "Point", "coordinates": [point.x(), point.y()]}),
+        Geometry::MultiPoint(points) => json!({
+            "type": "MultiPoint",
+            "coordinates": points.iter().map(|p| json!([p.x(), p.y()])).collect::<Vec<_>>(),
+        }),
+        Geometry::Line(line) => json!({
+            "type": "LineString",
+            "coordinates": [[line.start.x, line.start.y], [line.end.x, line.end.y]],
+        }),
+        Geometry::LineString(line) => {
+            json!({"type": "LineString", "coordinates": ring_coordinates(line)})
+        }
+        Geometry::MultiLineString(lines) => json!({
+            "type": "MultiLineString",
+            "coordinates": lines.iter().map(ring_coordinates).collect::<Vec<_>>(),
+        }),
+        Geometry::Polygon(polygon) => {
+            json!({"type": "Polygon", "coordinates": polygon_coordinates(polygon)})
+        }
+        Geometry::MultiPolygon(polygons) => json!({
+            "type": "MultiPolygon",
+            "coordinates": polygons.iter().map(polygon_coordinates).collect::<Vec<_>>(),
+        }),
+        Geometry::GeometryCollection(geometries) => json!({
+            "type": "GeometryCollection",
+            "geometries": geometries.iter().map(to_geojson).collect::<Vec<_>>(),
+        }),
+        Geometry::Rect(rect) => to_geojson(&Geometry::Polygon(rect.to_polygon())),
+        Geometry::Triangle(triangle) => to_geojson(&Geometry::Polygon(triangle.to_polygon())),
+    }
+}
+
+/// Polygons of a geometry, the other geometries having no area.
+pub fn polygons(geometry: &Geometry) -> Result<Vec<Polygon>, String> {
+    match geometry {
+        Geometry::Polygon(polygon) => Ok(vec![polygon.clone()]),
+        Geometry::MultiPolygon(polygons) => Ok(polygons.0.clone()),
+        Geometry::Rect(rect) => Ok(vec![rect.to_polygon()]),
+        Geometry::Triangle(triangle) => Ok(vec![triangle.to_polygon()]),
+        Geometry::GeometryCollection(geometries) => geometries
+            .iter()
+            .map(polygons)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|polygons| polygons.concat()),
+        _ => Err(String::from("the reference area should be a polygon")),
+    }
+}
+
+/// Unions polygons two by two, which is much faster than adding them one by one.
+pub fn union_all(mut parts: Vec<MultiPolygon>) -> MultiPolygon {
+    while parts.len() > 1 {
+        parts = parts
+            .chunks(2)
+            .map(|pair| match pair {
+                [first, second] => first.union(second),
+                _ => pair[0].clone(),
+            })
+            .collect();
+    }
+    parts.pop().unwrap_or_else(|| MultiPolygon::new(Vec::new()))
+}
+
+/// Number of segments of the circles approximating the round parts of the buffers.
+const BUFFER_SEGMENTS: usize = 32;
+
+fn disc(center: Coord, radius: f64) -> MultiPolygon {
+    let ring = (0..=BUFFER_SEGMENTS)
+        .map(|i| {
+            let angle = TAU * (i % BUFFER_SEGMENTS) as f64 / BUFFER_SEGMENTS as f64;
+            Coord {
+                x: center.x + radius * angle.cos(),
+                y: center.y + radius * angle.sin(),
+            }
+        })
+        .collect();
+    MultiPolygon::new(vec![Polygon::new(LineString::new(ring), vec![])])
+}
+
+/// Rectangle around a segment, without its round caps.
+fn band(line: Line, radius: f64) -> Option<MultiPolygon> {
+    let length = line.dx().hypot(line.dy());
+    if length == 0.0 {
+        return None;
+    }
+    let normal = Coord {
+        x: -line.dy() / length * radius,
+        y: line.dx() / length * radius,
+    };
+    let ring = vec![
+        line.start + normal,
+        line.end + normal,
+        line.end - normal,
+        line.start - normal,
+        line.start + normal,
+    ];
+    Some(MultiPolygon::new(vec![Polygon::new(
+        LineString::new(ring),
+        vec![],
+    )]))
+}
+
+/// Adds the polygons whose union is the buffer of a geometry.
+fn buffer_parts(geometry: &Geometry, distance: f64, parts: &mut Vec<MultiPolygon>) {
+    // One disc by vertex, the consecutive segments sharing theirs
+    let add_lines = |lines: Vec<Line>, parts: &mut Vec<MultiPolygon>| {
+        let mut vertices = lines
+            .iter()
+            .flat_map(|line| [line.start, line.end])
+            .collect::<Vec<_>>();
+        vertices.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        vertices.dedup();
+        parts.extend(vertices.into_iter().map(|vertex| disc(vertex, distance)));
+        parts.extend(lines.into_iter().filter_map(|line| band(line, distance)));
+    };
+    match geometry {
+        Geometry::Point(point) => parts.push(disc(point.0, distance)),
+        Geometry::MultiPoint(points) => {
+            parts.extend(points.iter().map(|point| disc(point.0, distance)))
+        }
+        Geometry::Line(line) => add_lines(vec![*line], parts),
+        Geometry::LineString(line) => add_lines(line.lines_iter().collect(), parts),
+        Geometry::MultiLineString(lines) => add_lines(lines.lines_iter().collect(), parts),
+        Geometry::Polygon(polygon) => {
+            parts.push(MultiPolygon::new(vec![polygon.clone()]));
+            add_lines(polygon.lines_iter().collect(), parts);
+        }
+        Geometry::MultiPolygon(polygons) => {
+            parts.push(polygons.clone());
+            add_lines(polygons.lines_iter().collect(), parts);
+        }
+        Geometry::Rect(rect) => {
+            buffer_parts(&Geometry::Polygon(rect.to_polygon()), distance, parts)
+        }
+        Geometry::Triangle(triangle) => {
+            buffer_parts(&Geometry::Polygon(triangle.to_polygon()), distance, parts)
+        }
+        Geometry::GeometryCollection(geometries) => {
+            for geometry in geometries {
+                buffer_parts(geometry, distance, parts);
+            }
+        }
+    }
+}
+
+/// Dilates a geometry by a distance, the circles being approximated by polygons.
+pub fn buffer(geometry: &Geometry, distance: f64) -> MultiPolygon {
+    let mut parts = Vec::new();
+    buffer_parts(geometry, distance, &mut parts);
+    union_all(parts)
+}
+
+#[test]
+fn test_geojson() {
+    let feature = json!({
+        "type": "FeatureCollection",
+        "features": [
+            {"type": "Feature", "properties": {}, "geometry": {
+                "type": "Polygon", "coordinates": [[[0, 0], [10, 0], [10, 10], [0, 0]]],
+            }},
+            {"type": "Feature", "properties": {}, "geometry": {
+                "type": "MultiPolygon", "coordinates": [[[[20, 0], [30, 0], [30, 10], [20, 0]]]],
+            }},
+        ],
+    });
+    let geometry = from_geojson(&feature).unwrap();
+    let Geometry::MultiPolygon(polygons) = &geometry else {
+        panic!("{geometry:?} should be a multipolygon");
+    };
+    assert_eq!(polygons.0.len(), 2);
+    assert_eq!(from_geojson(&to_geojson(&geometry)).unwrap(), geometry);
+    assert!(from_geojson(&json!({"type": "Circle"})).is_err());
+}
+
+#[test]
+fn test_buffer() {
+    use geo::{Area, Contains};
+
+    let road: Geometry = LineString::from(vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0)]).into();
+    let buffered = buffer(&road, 10.0);
+    assert!(buffered.contains(&Point::new(50.0, 9.0)));
+    assert!(buffered.contains(&Point::new(107.0, 107.0)));
+    assert!(!buffered.contains(&Point::new(50.0, 11.0)));
+    // Two bands of 100 x 20, and the round caps and corners
+    let area = buffered.unsigned_area();
+    assert!(
+        4000.0 < area && area < 4000.0 + std::f64::consts::PI * 100.0,
+        "{area}"
+    );
+
+    // A zigzag of 200 vertices, whose buffer is a band with its round caps
+    let zigzag: Geometry = LineString::from(
+        (0..200)
+            .map(|i| (i as f64, (i % 2) as f64 * 0.1))
+            .collect::<Vec<_>>(),
+    )
+    .into();
+    let buffered = buffer(&zigzag, 5.0);
+    assert_eq!(buffered.0.len(), 1);
+    assert!(buffered.contains(&Point::new(100.0, 5.0)));
+    assert!(buffered.contains(&Point::new(-4.9, 0.0)));
+    assert!(!buffered.contains(&Point::new(100.0, 6.0)));
+    let area = buffered.unsigned_area();
+    let expected = 199.0 * 10.0 + std::f64::consts::PI * 25.0;
+    assert!((area - expected).abs() < 0.02 * expected, "{area}");
+}
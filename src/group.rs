@@ -0,0 +1,189 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::header::MyHeader;
+
+/// Counts and number of points of the files sharing the same attribute values.
+#[derive(Debug)]
+pub struct Group {
+    pub values: Vec<Value>,
+    pub file_count: u64,
+    pub total_points: u64,
+}
+
+/// Aggregation of the selected files by distinct combinations of header attributes.
+#[derive(Debug)]
+pub struct GroupBy {
+    pub attributes: Vec<String>,
+    pub groups: BTreeMap<Vec<String>, Group>,
+}
+
+/// Checks an attribute name given to --group-by (used to validate command line arguments).
+pub fn check_attribute(name: &str) -> Result<String, String> {
+    match MyHeader::is_attribute(name) {
+        true => Ok(name.to_string()),
+        false => Err(format!("unknown header attribute \"{name}\"")),
+    }
+}
+
+/// Formats a JSON value as a plain string (strings are not quoted, null is empty).
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+/// Orders the values of two groups, numbers by value and the other values as strings.
+fn compare_values(first: &[Value], second: &[Value]) -> Ordering {
+    first
+        .iter()
+        .zip(second)
+        .map(|(a, b)| match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            _ => plain(a).cmp(&plain(b)),
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Quotes a CSV field when needed.
+pub fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+impl GroupBy {
+    pub fn new(attributes: Vec<String>) -> GroupBy {
+        GroupBy {
+            attributes,
+            groups: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, header: &MyHeader) {
+        let values = self
+            .attributes
+            .iter()
+            .map(|name| header.attribute_value(name).unwrap_or(Value::Null))
+            .collect::<Vec<_>>();
+        let key = values.iter().map(plain).collect::<Vec<_>>();
+
+        let group = self.groups.entry(key).or_insert(Group {
+            values,
+            file_count: 0,
+            total_points: 0,
+        });
+        group.file_count += 1;
+        group.total_points += header.number_of_points;
+    }
+
+    /// Groups sorted by their values.
+    fn sorted_groups(&self) -> Vec<&Group> {
+        let mut groups = self.groups.values().collect::<Vec<_>>();
+        groups.sort_by(|a, b| compare_values(&a.values, &b.values));
+        groups
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        let mut rows = vec![self
+            .attributes
+            .iter()
+            .cloned()
+            .chain([String::from("files"), String::from("points")])
+            .collect::<Vec<_>>()];
+        for group in self.sorted_groups() {
+            rows.push(
+                group
+                    .values
+                    .iter()
+                    .map(plain)
+                    .chain([group.file_count.to_string(), group.total_points.to_string()])
+                    .collect(),
+            );
+        }
+        rows
+    }
+
+    pub fn to_text(&self) -> String {
+        let rows = self.rows();
+        let widths = (0..rows[0].len())
+            .map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap())
+            .collect::<Vec<_>>();
+
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .zip(&widths)
+                    .map(|(field, width)| format!("{field:<width$}"))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn to_csv(&self) -> String {
+        self.rows()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|field| csv_field(field))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::Array(
+            self.sorted_groups()
+                .into_iter()
+                .map(|group| {
+                    let mut object = Map::new();
+                    for (name, value) in self.attributes.iter().zip(&group.values) {
+                        object.insert(name.clone(), value.clone());
+                    }
+                    object.insert(String::from("files"), json!(group.file_count));
+                    object.insert(String::from("points"), json!(group.total_points));
+                    Value::Object(object)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_group_by() {
+    let header_12 = MyHeader::new(&las::Builder::from((1, 2)).into_header().unwrap());
+    let header_14 = MyHeader::new(&las::Builder::from((1, 4)).into_header().unwrap());
+
+    let mut group_by = GroupBy::new(vec![String::from("version")]);
+    group_by.add(&header_12);
+    group_by.add(&header_14);
+    group_by.add(&header_14);
+
+    assert_eq!(group_by.to_csv(), "version,files,points\n1.2,1,0\n1.4,2,0");
+
+    // Numbers are sorted by value, not as strings
+    let mut group_by = GroupBy::new(vec![String::from("file_source_id")]);
+    for file_source_id in [10, 9] {
+        let mut builder = las::Builder::from((1, 4));
+        builder.file_source_id = file_source_id;
+        group_by.add(&MyHeader::new(&builder.into_header().unwrap()));
+    }
+    assert_eq!(
+        group_by.to_csv(),
+        "file_source_id,files,points\n9,1,0\n10,1,0"
+    );
+    assert!(check_attribute("generating_software").is_ok());
+    assert!(check_attribute("vlrs").is_err());
+}
@@ -1,10 +1,148 @@
-use chrono::NaiveDate;
-use geo::{Centroid, LineString, Polygon};
-use las::Bounds;
+use chrono::{Datelike, NaiveDate};
+use geo::{Centroid, Geometry, LineString, Polygon};
+use las::{raw, Bounds, Read, Reader, Transform, Vector};
+use regex::Regex;
 use semver::Version;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Seek, SeekFrom};
+use std::path::Path;
 use uuid::Uuid;
 
+use crate::footprint::FootprintSource;
+use crate::scan::PointStats;
+use crate::tile_name::NAME_PREFIX;
+use crate::validate::Issue;
 
+/// Accessor returning the value of a numeric header attribute, if the header has one.
+pub type NumericAccessor = fn(&MyHeader) -> Option<f64>;
+
+/// Registry of the numeric attributes of [`MyHeader`], indexed by their filter name.
+pub const NUMERIC_FIELDS: &[(&str, NumericAccessor)] = &[
+    ("number_of_points", |h| Some(h.number_of_points as f64)),
+    ("file_source_id", |h| Some(h.file_source_id as f64)),
+    ("point_data_record_length", |h| {
+        Some(h.point_data_record_length as f64)
+    }),
+    ("header_size", |h| h.header_size.map(|v| v as f64)),
+    ("offset_to_point_data", |h| {
+        h.offset_to_point_data.map(|v| v as f64)
+    }),
+    ("number_of_vlrs", |h| Some(h.number_of_vlrs as f64)),
+    ("min_x", |h| Some(h.bounds.min.x)),
+    ("min_y", |h| Some(h.bounds.min.y)),
+    ("min_z", |h| Some(h.bounds.min.z)),
+    ("max_x", |h| Some(h.bounds.max.x)),
+    ("max_y", |h| Some(h.bounds.max.y)),
+    ("max_z", |h| Some(h.bounds.max.z)),
+    ("scale_x", |h| Some(h.transforms.x.scale)),
+    ("scale_y", |h| Some(h.transforms.y.scale)),
+    ("scale_z", |h| Some(h.transforms.z.scale)),
+    ("offset_x", |h| Some(h.transforms.x.offset)),
+    ("offset_y", |h| Some(h.transforms.y.offset)),
+    ("offset_z", |h| Some(h.transforms.z.offset)),
+    ("day_of_year", |h| h.day_of_year.map(|v| v as f64)),
+    ("year", |h| h.year.map(|v| v as f64)),
+    ("epsg", |h| h.epsg.map(|v| v as f64)),
+    ("copc_spacing", |h| h.copc_spacing),
+    ("extra_bytes", |h| Some(h.point_format.extra_bytes as f64)),
+    ("scanned_points", |h| {
+        h.points.as_ref().map(|p| p.points_read as f64)
+    }),
+    ("scanned_min_x", |h| h.scanned_bounds().map(|b| b.min.x)),
+    ("scanned_min_y", |h| h.scanned_bounds().map(|b| b.min.y)),
+    ("scanned_min_z", |h| h.scanned_bounds().map(|b| b.min.z)),
+    ("scanned_max_x", |h| h.scanned_bounds().map(|b| b.max.x)),
+    ("scanned_max_y", |h| h.scanned_bounds().map(|b| b.max.y)),
+    ("scanned_max_z", |h| h.scanned_bounds().map(|b| b.max.z)),
+];
+
+/// Accessor returning the range of values of a point attribute, if the points were scanned.
+pub type RangeAccessor = fn(&MyHeader) -> Option<(f64, f64)>;
+
+/// Registry of the ranges of point attributes, indexed by their filter name.
+pub const RANGE_FIELDS: &[(&str, RangeAccessor)] = &[
+    ("intensity", |h| {
+        let (min, max) = h.points.as_ref()?.intensity?;
+        Some((min as f64, max as f64))
+    }),
+    ("gps_time", |h| h.points.as_ref()?.gps_time),
+];
+
+/// Attributes computed from the points, only known with --scan-points.
+pub const POINT_ATTRIBUTES: &[&str] = &[
+    "has_class",
+    "hull",
+    "intensity",
+    "gps_time",
+    "scanned_points",
+    "scanned_min_x",
+    "scanned_min_y",
+    "scanned_min_z",
+    "scanned_max_x",
+    "scanned_max_y",
+    "scanned_max_z",
+];
+
+/// Accessor returning the value of a boolean header attribute.
+pub type BoolAccessor = fn(&MyHeader) -> bool;
+
+/// Registry of the boolean attributes of [`MyHeader`], indexed by their filter name.
+pub const BOOL_FIELDS: &[(&str, BoolAccessor)] = &[
+    ("has_gps_time", |h| h.point_format.has_gps_time),
+    ("has_color", |h| h.point_format.has_color),
+    ("has_nir", |h| h.point_format.has_nir),
+    ("has_waveform", |h| h.point_format.has_waveform),
+    ("extended", |h| h.point_format.is_extended),
+    ("has_synthetic_return_numbers", |h| {
+        h.has_synthetic_return_numbers
+    }),
+    ("valid", |h| {
+        h.issues.as_ref().is_none_or(|issues| issues.is_empty())
+    }),
+];
+
+/// Accessor returning the value of a string header attribute, if the header has one.
+pub type StringAccessor = fn(&MyHeader) -> Option<String>;
+
+/// Registry of the string attributes of [`MyHeader`], indexed by their name.
+pub const STRING_FIELDS: &[(&str, StringAccessor)] = &[
+    ("version", |h| Some(h.version_name())),
+    ("gps_time_type", |h| {
+        Some(h.gps_time_type_name().to_string())
+    }),
+    ("guid", |h| Some(h.guid.to_string())),
+    ("system_identifier", |h| Some(h.system_identifier.clone())),
+    ("generating_software", |h| {
+        Some(h.generating_software.clone())
+    }),
+    ("date", |h| h.date.map(|date| date.to_string())),
+];
+
+/// Description of a (extended) variable length record, without its data.
+#[derive(Clone)]
+pub struct VlrSummary {
+    pub user_id: String,
+    pub record_id: u16,
+    pub description: String,
+    pub data_length: usize,
+    pub extended: bool,
+}
+
+impl VlrSummary {
+    fn new(vlr: &las::Vlr, extended: bool) -> VlrSummary {
+        VlrSummary {
+            user_id: vlr.user_id.clone(),
+            record_id: vlr.record_id,
+            description: vlr.description.clone(),
+            data_length: vlr.data.len(),
+            extended,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct MyHeader {
     pub file_source_id: u16,
     pub gps_time_type: las::GpsTimeType,
@@ -14,16 +152,38 @@ pub struct MyHeader {
     pub system_identifier: String,
     pub generating_software: String,
     pub date: Option<NaiveDate>,
+    pub day_of_year: Option<u16>,
+    pub year: Option<u16>,
     pub point_format: las::point::Format,
-    //transform
+    pub point_data_record_length: u16,
+    /// Size of the header, as written in the file (unknown for headers not read from a file)
+    pub header_size: Option<u16>,
+    /// Offset of the points, as written in the file (unknown for headers not read from a file)
+    pub offset_to_point_data: Option<u32>,
+    pub number_of_vlrs: u32,
+    pub epsg: Option<u32>,
+    pub copc_spacing: Option<f64>,
+    pub transforms: Vector<Transform>,
+    pub bounds: Bounds,
     pub bbox: Polygon,
     pub centroid: geo::Point,
     pub number_of_points: u64,
+    /// Number of points by return number, starting at the first return
+    pub number_of_points_by_return: Vec<u64>,
+    pub vlrs: Vec<VlrSummary>,
+    /// Statistics of the points, when they are scanned
+    pub points: Option<PointStats>,
+    /// Inconsistencies of the header, when it is validated
+    pub issues: Option<Vec<Issue>>,
+    /// Named captures of the file name, with --name-pattern
+    pub name_fields: Option<BTreeMap<String, String>>,
+    /// Footprint read from the sidecar file of the tile, with --footprint sidecar
+    pub sidecar: Option<Geometry>,
 }
 
 impl MyHeader {
     fn bounds2poly(las_bounds: Bounds) -> Polygon {
-        let poly: Polygon = Polygon::new(
+        Polygon::new(
             LineString::from(vec![
                 (las_bounds.min.x, las_bounds.min.y),
                 (las_bounds.min.x, las_bounds.max.y),
@@ -32,16 +192,280 @@ impl MyHeader {
                 (las_bounds.min.x, las_bounds.min.y),
             ]),
             vec![],
-        );
+        )
+    }
+
+    /// Reads the EPSG code of the CRS, from the GeoTIFF keys or from the WKT VLR.
+    fn epsg(las_header: &las::Header) -> Option<u32> {
+        let projection_vlr = |record_id| {
+            las_header
+                .all_vlrs()
+                .find(|vlr| vlr.user_id == "LASF_Projection" && vlr.record_id == record_id)
+        };
 
-        return poly;
+        if let Some(vlr) = projection_vlr(2112) {
+            // The authority of the root CRS is the last one of the WKT
+            let re = Regex::new(r#"(?:AUTHORITY|ID)\["EPSG",\s*"?(\d+)"?\]"#).unwrap();
+            let wkt = String::from_utf8_lossy(&vlr.data);
+            if let Some(code) = re.captures_iter(&wkt).last() {
+                return code[1].parse().ok();
+            }
+        }
+
+        let vlr = projection_vlr(34735)?;
+        let keys = vlr
+            .data
+            .chunks_exact(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect::<Vec<_>>();
+        // Keys are (id, location, count, value) after a 4 values header, the value being
+        // stored inline when location is 0.
+        let key = |id| {
+            keys.get(4..)?
+                .chunks_exact(4)
+                .find(|key| key[0] == id && key[1] == 0)
+                .map(|key| key[3] as u32)
+        };
+        // ProjectedCSTypeGeoKey, then GeographicTypeGeoKey
+        key(3072).or_else(|| key(2048))
+    }
+
+    /// Reads the octree spacing of the COPC info VLR, if the file is a COPC.
+    fn copc_spacing(las_header: &las::Header) -> Option<f64> {
+        let vlr = las_header
+            .vlrs()
+            .iter()
+            .find(|vlr| vlr.user_id == "copc" && vlr.record_id == 1)?;
+        let bytes = vlr.data.get(32..40)?;
+        Some(f64::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn number_of_points_by_return(las_header: &las::Header) -> Vec<u64> {
+        let mut counts = (1..=15)
+            .map(|n| las_header.number_of_points_by_return(n).unwrap_or(0))
+            .collect::<Vec<_>>();
+        while counts.last() == Some(&0) {
+            counts.pop();
+        }
+        counts
+    }
+
+    /// Name of the gps time type, as used by the filters ("week" or "standard").
+    pub fn gps_time_type_name(&self) -> &'static str {
+        match self.gps_time_type.is_standard() {
+            true => "standard",
+            false => "week",
+        }
+    }
+
+    /// Las version, formatted as "major.minor".
+    pub fn version_name(&self) -> String {
+        format!("{}.{}", self.version.major, self.version.minor)
+    }
+
+    /// Returns every attribute of the header as a JSON object.
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "version": self.version_name(),
+            "file_source_id": self.file_source_id,
+            "gps_time_type": self.gps_time_type_name(),
+            "has_synthetic_return_numbers": self.has_synthetic_return_numbers,
+            "guid": self.guid.to_string(),
+            "system_identifier": self.system_identifier,
+            "generating_software": self.generating_software,
+            "date": self.date.map(|date| date.to_string()),
+            "day_of_year": self.day_of_year,
+            "year": self.year,
+            "point_format": self.point_format.to_u8().ok(),
+            "point_data_record_length": self.point_data_record_length,
+            "extra_bytes": self.point_format.extra_bytes,
+            "has_gps_time": self.point_format.has_gps_time,
+            "has_color": self.point_format.has_color,
+            "has_nir": self.point_format.has_nir,
+            "has_waveform": self.point_format.has_waveform,
+            "extended": self.point_format.is_extended,
+            "compressed": self.point_format.is_compressed,
+            "header_size": self.header_size,
+            "offset_to_point_data": self.offset_to_point_data,
+            "number_of_vlrs": self.number_of_vlrs,
+            "number_of_points": self.number_of_points,
+            "number_of_points_by_return": self.number_of_points_by_return,
+            "scale": [self.transforms.x.scale, self.transforms.y.scale, self.transforms.z.scale],
+            "offset": [self.transforms.x.offset, self.transforms.y.offset, self.transforms.z.offset],
+            "min": [self.bounds.min.x, self.bounds.min.y, self.bounds.min.z],
+            "max": [self.bounds.max.x, self.bounds.max.y, self.bounds.max.z],
+            "epsg": self.epsg,
+            "copc_spacing": self.copc_spacing,
+            "vlrs": self.vlrs.iter().map(|vlr| json!({
+                "user_id": vlr.user_id,
+                "record_id": vlr.record_id,
+                "description": vlr.description,
+                "data_length": vlr.data_length,
+                "extended": vlr.extended,
+            })).collect::<Vec<_>>(),
+        });
+        if let (Some(object), Some(points)) = (value.as_object_mut(), &self.points) {
+            object.insert(String::from("points"), points.to_json());
+        }
+        if let (Some(object), Some(fields)) = (value.as_object_mut(), &self.name_fields) {
+            object.insert(String::from("name_fields"), json!(fields));
+        }
+        if let (Some(object), Some(issues)) = (value.as_object_mut(), &self.issues) {
+            object.insert(String::from("valid"), Value::Bool(issues.is_empty()));
+            let issues = issues.iter().map(Issue::to_json).collect();
+            object.insert(String::from("issues"), Value::Array(issues));
+        }
+        value
+    }
+
+    /// Returns false when the header has no value for the attribute `name`.
+    ///
+    /// The name fields are missing only without name pattern: a file whose name doesn't match
+    /// the pattern has them, with no value matching a condition.
+    pub fn has_attribute(&self, name: &str) -> bool {
+        match name {
+            "date" => self.date.is_some(),
+            "has_class" => self.points.is_some(),
+            "valid" => self.issues.is_some(),
+            "hull" | "sidecar" => name
+                .parse::<FootprintSource>()
+                .is_ok_and(|source| self.footprint(source).is_some()),
+            _ if name.starts_with(NAME_PREFIX) => self.name_fields.is_some(),
+            _ => match (Self::numeric_field(name), Self::range_field(name)) {
+                (Some(accessor), _) => accessor(self).is_some(),
+                (_, Some(accessor)) => accessor(self).is_some(),
+                (None, None) => true,
+            },
+        }
+    }
+
+    /// Returns the footprint of the file compared by the spatial filters, if it is known.
+    pub fn footprint(&self, source: FootprintSource) -> Option<Geometry> {
+        match source {
+            FootprintSource::Bbox => Some(Geometry::Polygon(self.bbox.clone())),
+            FootprintSource::Hull => self.points.as_ref()?.hull().map(Geometry::Polygon),
+            FootprintSource::Sidecar => self.sidecar.clone(),
+        }
+    }
+
+    /// Returns the value captured from the file name for the attribute `name` (eg. "name.x").
+    pub fn name_field(&self, name: &str) -> Option<&str> {
+        let field = name.strip_prefix(NAME_PREFIX)?;
+        self.name_fields.as_ref()?.get(field).map(String::as_str)
+    }
+
+    /// Returns the value of the numeric attribute `name`, a registry field or a name capture.
+    pub fn numeric_value(&self, name: &str) -> Option<f64> {
+        match Self::numeric_field(name) {
+            Some(accessor) => accessor(self),
+            None => self.name_field(name)?.parse().ok(),
+        }
+    }
+
+    /// Returns the accessor of the range of the point attribute `name`, if it exists.
+    pub fn range_field(name: &str) -> Option<RangeAccessor> {
+        RANGE_FIELDS
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, accessor)| *accessor)
+    }
+
+    /// Returns the accessor of the numeric attribute `name`, if it exists.
+    pub fn numeric_field(name: &str) -> Option<NumericAccessor> {
+        NUMERIC_FIELDS
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, accessor)| *accessor)
+    }
+
+    /// Returns the accessor of the string attribute `name`, if it exists.
+    pub fn string_field(name: &str) -> Option<StringAccessor> {
+        STRING_FIELDS
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, accessor)| *accessor)
+    }
+
+    /// Returns true if `name` is a single valued attribute (eg. not the vlrs or the bounds).
+    pub fn is_attribute(name: &str) -> bool {
+        matches!(name, "point_format" | "compressed")
+            || name.starts_with(NAME_PREFIX)
+            || Self::string_field(name).is_some()
+            || Self::numeric_field(name).is_some()
+            || Self::bool_field(name).is_some()
+    }
+
+    /// Returns the value of the attribute `name`, null when the header has none, `None` when
+    /// it is not an attribute.
+    pub fn attribute_value(&self, name: &str) -> Option<Value> {
+        // Integers are not written as floats
+        let number = |value: f64| match value.fract() == 0.0 && value.abs() < 2f64.powi(53) {
+            true => json!(value as i64),
+            false => json!(value),
+        };
+        let value = match name {
+            "point_format" => json!(self.point_format.to_u8().ok()),
+            "compressed" => json!(self.point_format.is_compressed),
+            _ if name.starts_with(NAME_PREFIX) => json!(self.name_field(name)),
+            _ => match (
+                Self::string_field(name),
+                Self::numeric_field(name),
+                Self::bool_field(name),
+            ) {
+                (Some(accessor), _, _) => json!(accessor(self)),
+                (_, Some(accessor), _) => accessor(self).map_or(Value::Null, number),
+                (_, _, Some(accessor)) => json!(accessor(self)),
+                (None, None, None) => return None,
+            },
+        };
+        Some(value)
+    }
+
+    /// Returns the accessor of the boolean attribute `name`, if it exists.
+    pub fn bool_field(name: &str) -> Option<BoolAccessor> {
+        BOOL_FIELDS
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, accessor)| *accessor)
+    }
+
+    /// Opens a file, and reads the sizes and offsets of its raw header, which the las crate
+    /// recomputes.
+    fn open(path: &Path) -> Result<(Reader<'static>, MyHeader), String> {
+        let error = |error: &dyn std::fmt::Display| {
+            format!("Impossible to read {}: {error}", path.display())
+        };
+        let mut file = BufReader::new(File::open(path).map_err(|e| error(&e))?);
+        let raw_header = raw::Header::read_from(&mut file).map_err(|e| error(&e))?;
+        file.seek(SeekFrom::Start(0)).map_err(|e| error(&e))?;
+        let reader = Reader::new(file).map_err(|e| error(&e))?;
+
+        let mut header = MyHeader::new(reader.header());
+        header.header_size = Some(raw_header.header_size);
+        header.offset_to_point_data = Some(raw_header.offset_to_point_data);
+        Ok((reader, header))
+    }
+
+    /// Reads the header of a las or laz file.
+    pub fn from_path(path: &Path) -> Result<MyHeader, String> {
+        MyHeader::open(path).map(|(_, header)| header)
+    }
+
+    /// Reads the header of a file and scans one point every `every` points.
+    pub fn from_path_scanned(path: &Path, every: u64) -> Result<MyHeader, String> {
+        let (mut reader, mut header) = MyHeader::open(path)?;
+        header.points = Some(PointStats::scan(&mut reader, every).map_err(|error| {
+            format!(
+                "Impossible to read the points of {}: {error}",
+                path.display()
+            )
+        })?);
+        Ok(header)
     }
 
     pub fn new(las_header: &las::Header) -> MyHeader {
         let bbox_poly = Self::bounds2poly(las_header.bounds());
 
-        //println!("{:?}", las_header.point_format().to_u8());
-
         MyHeader {
             file_source_id: las_header.file_source_id(),
             gps_time_type: las_header.gps_time_type(),
@@ -55,11 +479,40 @@ impl MyHeader {
             system_identifier: las_header.system_identifier().to_string(),
             generating_software: las_header.generating_software().to_string(),
             date: las_header.date(),
-            point_format: las_header.point_format().clone(),
-            //transform
+            day_of_year: las_header.date().map(|d| d.ordinal() as u16),
+            year: las_header.date().map(|d| d.year() as u16),
+            point_format: *las_header.point_format(),
+            point_data_record_length: las_header.point_format().len(),
+            header_size: None,
+            offset_to_point_data: None,
+            number_of_vlrs: las_header.vlrs().len() as u32,
+            epsg: Self::epsg(las_header),
+            copc_spacing: Self::copc_spacing(las_header),
+            transforms: *las_header.transforms(),
+            bounds: las_header.bounds(),
             bbox: bbox_poly.clone(),
             centroid: bbox_poly.centroid().unwrap(),
             number_of_points: las_header.number_of_points(),
+            number_of_points_by_return: Self::number_of_points_by_return(las_header),
+            vlrs: las_header
+                .vlrs()
+                .iter()
+                .map(|vlr| VlrSummary::new(vlr, false))
+                .chain(
+                    las_header
+                        .evlrs()
+                        .iter()
+                        .map(|vlr| VlrSummary::new(vlr, true)),
+                )
+                .collect(),
+            points: None,
+            issues: None,
+            name_fields: None,
+            sidecar: None,
         }
     }
+
+    fn scanned_bounds(&self) -> Option<Bounds> {
+        self.points.as_ref()?.bounds
+    }
 }
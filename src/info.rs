@@ -0,0 +1,198 @@
+use crate::header::MyHeader;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Formats the header of a file in a human readable layout (like `lasinfo`).
+pub fn header_text(path: &str, header: &MyHeader) -> String {
+    let optional = |value: Option<String>| value.unwrap_or(String::from("-"));
+    let xyz = |x: f64, y: f64, z: f64| format!("{x} {y} {z}");
+
+    let mut lines = vec![
+        path.to_string(),
+        format!("  Version:                    {}", header.version_name()),
+        format!("  System identifier:          {}", header.system_identifier),
+        format!(
+            "  Generating software:        {}",
+            header.generating_software
+        ),
+        format!("  File source id:             {}", header.file_source_id),
+        format!("  GUID:                       {}", header.guid),
+        format!(
+            "  Creation date:              {}",
+            optional(header.date.map(|date| format!(
+                "{date} (day {} of {})",
+                header.day_of_year.unwrap_or_default(),
+                header.year.unwrap_or_default()
+            )))
+        ),
+        format!(
+            "  GPS time type:              {}",
+            header.gps_time_type_name()
+        ),
+        format!(
+            "  Synthetic return numbers:   {}",
+            header.has_synthetic_return_numbers
+        ),
+        format!(
+            "  Header size:                {}",
+            optional(header.header_size.map(|size| size.to_string()))
+        ),
+        format!(
+            "  Offset to point data:       {}",
+            optional(header.offset_to_point_data.map(|offset| offset.to_string()))
+        ),
+        format!("  Number of VLRs:             {}", header.number_of_vlrs),
+        format!(
+            "  Point format:               {}{}",
+            optional(header.point_format.to_u8().ok().map(|n| n.to_string())),
+            match header.point_format.is_compressed {
+                true => " (compressed)",
+                false => "",
+            }
+        ),
+        format!(
+            "  Point record length:        {}",
+            header.point_data_record_length
+        ),
+        format!(
+            "  Extra bytes:                {}",
+            header.point_format.extra_bytes
+        ),
+        format!("  Number of points:           {}", header.number_of_points),
+        format!(
+            "  Scale factor x y z:         {}",
+            xyz(
+                header.transforms.x.scale,
+                header.transforms.y.scale,
+                header.transforms.z.scale
+            )
+        ),
+        format!(
+            "  Offset x y z:               {}",
+            xyz(
+                header.transforms.x.offset,
+                header.transforms.y.offset,
+                header.transforms.z.offset
+            )
+        ),
+        format!(
+            "  Min x y z:                  {}",
+            xyz(
+                header.bounds.min.x,
+                header.bounds.min.y,
+                header.bounds.min.z
+            )
+        ),
+        format!(
+            "  Max x y z:                  {}",
+            xyz(
+                header.bounds.max.x,
+                header.bounds.max.y,
+                header.bounds.max.z
+            )
+        ),
+        format!(
+            "  CRS:                        {}",
+            optional(header.epsg.map(|code| format!("EPSG:{code}")))
+        ),
+    ];
+
+    if let Some(spacing) = header.copc_spacing {
+        lines.push(format!("  COPC spacing:               {spacing}"));
+    }
+
+    lines.push(String::from("  Points by return:"));
+    for (index, count) in header.number_of_points_by_return.iter().enumerate() {
+        lines.push(format!("    {:>2}: {count}", index + 1));
+    }
+
+    lines.push(String::from("  VLRs:"));
+    for vlr in &header.vlrs {
+        lines.push(format!(
+            "    {}{} {} \"{}\" ({} bytes)",
+            match vlr.extended {
+                true => "(extended) ",
+                false => "",
+            },
+            vlr.user_id,
+            vlr.record_id,
+            vlr.description,
+            vlr.data_length
+        ));
+    }
+
+    if let Some(points) = &header.points {
+        let range =
+            |range: Option<(f64, f64)>| optional(range.map(|(min, max)| format!("{min} {max}")));
+        lines.push(String::from("  Points scanned:"));
+        lines.push(format!(
+            "    Points read:              {}",
+            points.points_read
+        ));
+        if let Some(bounds) = points.bounds {
+            lines.push(format!(
+                "    Min x y z:                {}",
+                xyz(bounds.min.x, bounds.min.y, bounds.min.z)
+            ));
+            lines.push(format!(
+                "    Max x y z:                {}",
+                xyz(bounds.max.x, bounds.max.y, bounds.max.z)
+            ));
+        }
+        lines.push(format!(
+            "    Intensity min max:        {}",
+            range(points.intensity.map(|(min, max)| (min as f64, max as f64)))
+        ));
+        lines.push(format!(
+            "    GPS time min max:         {}",
+            range(points.gps_time)
+        ));
+        lines.push(String::from("    Classifications:"));
+        for (class, count) in &points.classes {
+            lines.push(format!("      {class:>3}: {count}"));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the header of a file as a JSON object, with its path.
+pub fn header_json(path: &str, header: &MyHeader) -> Value {
+    let mut value = json!({ "path": path });
+    if let (Some(object), Value::Object(attributes)) = (value.as_object_mut(), header.to_json()) {
+        object.extend(attributes);
+    }
+    value
+}
+
+/// Prints the header of each file, as text or as a JSON array, and returns the number of files
+/// which can't be read.
+pub fn print_info(paths: &[&str], format: &str, scan_points: Option<u64>) -> usize {
+    let mut blocks = Vec::new();
+    let mut values = Vec::new();
+    let mut errors = 0;
+
+    for path in paths {
+        let header = match scan_points {
+            Some(every) => MyHeader::from_path_scanned(Path::new(path), every),
+            None => MyHeader::from_path(Path::new(path)),
+        };
+        match header {
+            Ok(header) => match format {
+                "json" => values.push(header_json(path, &header)),
+                _ => blocks.push(header_text(path, &header)),
+            },
+            Err(error) => {
+                eprintln!("{error}");
+                errors += 1;
+            }
+        }
+    }
+
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&values).unwrap()),
+        _ if blocks.is_empty() => {}
+        _ => println!("{}", blocks.join("\n\n")),
+    }
+    errors
+}
@@ -0,0 +1,38 @@
+//! lg (LasGrep) filters asprs's las and laz files with the informations contained in their
+//! header.
+//!
+//! ```no_run
+//! use lg::{find, LasHeaderFilter, WalkOptions};
+//!
+//! let filter: LasHeaderFilter = "point_format in {6,7} and date >=2023".parse().unwrap();
+//! for file in find("./las_folder", &WalkOptions::default(), &filter).flatten() {
+//!     println!("{}: {} points", file.path.display(), file.header.number_of_points);
+//! }
+//! ```
+
+pub mod bool_filter;
+pub mod coverage;
+pub mod date_filter;
+pub mod diff;
+pub mod duplicates;
+pub mod filter;
+pub mod footprint;
+pub mod geometry;
+pub mod group;
+pub mod header;
+pub mod info;
+pub mod list_filter;
+pub mod numeric_filter;
+pub mod query;
+pub mod scan;
+pub mod sort;
+pub mod spatial_filter;
+pub mod stats;
+pub mod string_filter;
+pub mod tile_name;
+pub mod validate;
+pub mod walk;
+
+pub use filter::{Compare, Filter, LasHeaderFilter, MissingPolicy, MissingValueError, Verdict};
+pub use header::MyHeader;
+pub use walk::{find, walk, MatchedFile, Walk, WalkOptions};
@@ -1,5 +1,13 @@
 use crate::filter::Compare;
 use crate::header::MyHeader;
+use log::warn;
+use std::fmt::{Display, Formatter};
+
+/// Highest point format defined by the las specification (1.4).
+pub const MAX_POINT_FORMAT: u8 = 10;
+
+/// Bits flagging compressed point formats in laz files.
+pub const COMPRESSION_MASK: u8 = 0xc0;
 
 #[derive(Debug)]
 pub struct ListFilter {
@@ -7,8 +15,88 @@ pub struct ListFilter {
     header_attribute: String,
 }
 
+/// Parses a point format (eg. "6") or an inclusive range of point formats (eg. "6-10").
+///
+/// Compression bits set by laz writers (eg. "134" for a compressed format 6) are ignored.
+pub fn parse_point_formats(input: &str) -> Result<Vec<u8>, String> {
+    let parse_format = |format: &str| {
+        let format = format.trim();
+        match format.parse::<u8>() {
+            Ok(n) if n & !COMPRESSION_MASK <= MAX_POINT_FORMAT => Ok(n & !COMPRESSION_MASK),
+            Ok(n) => Err(format!("point format {n} is not in 0-{MAX_POINT_FORMAT}")),
+            Err(_) => Err(format!("invalid point format \"{format}\"")),
+        }
+    };
+
+    match input.split_once("..").or_else(|| input.split_once('-')) {
+        Some((first, last)) => {
+            let (first, last) = (parse_format(first)?, parse_format(last)?);
+            match first <= last {
+                true => Ok((first..=last).collect()),
+                false => Err(format!("empty point format range \"{input}\"")),
+            }
+        }
+        None => Ok(vec![parse_format(input)?]),
+    }
+}
+
+/// Parses a classification (eg. "9") or an inclusive range of classifications (eg. "3-5").
+pub fn parse_classes(input: &str) -> Result<Vec<u8>, String> {
+    let parse_class = |class: &str| {
+        let class = class.trim();
+        class
+            .parse::<u8>()
+            .map_err(|_| format!("invalid classification \"{class}\""))
+    };
+
+    match input.split_once("..").or_else(|| input.split_once('-')) {
+        Some((first, last)) => {
+            let (first, last) = (parse_class(first)?, parse_class(last)?);
+            match first <= last {
+                true => Ok((first..=last).collect()),
+                false => Err(format!("empty classification range \"{input}\"")),
+            }
+        }
+        None => Ok(vec![parse_class(input)?]),
+    }
+}
+
+/// Parses a list of values, eg. "in {6,7}", "[0..2, 6]" or "6-10", with `parse_item`
+/// parsing each value or range of values.
+fn parse_list(
+    input: &str,
+    parse_item: fn(&str) -> Result<Vec<u8>, String>,
+) -> Result<Vec<u8>, String> {
+    let input = input.trim();
+    let input = input.strip_prefix("in").unwrap_or(input).trim();
+    let list = match (input.chars().next(), input.chars().last()) {
+        (Some('{'), Some('}')) | (Some('['), Some(']')) => &input[1..input.len() - 1],
+        _ => input,
+    };
+
+    let mut values = Vec::new();
+    for item in list.split(',') {
+        for value in parse_item(item)? {
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Parses a list of point formats, eg. "in {6,7}", "[0..2, 6]" or "6-10".
+pub fn parse_point_format_list(input: &str) -> Result<Vec<u8>, String> {
+    parse_list(input, parse_point_formats)
+}
+
+/// Parses a list of classifications, eg. "in {2,9}" or "[3-5]".
+pub fn parse_class_list(input: &str) -> Result<Vec<u8>, String> {
+    parse_list(input, parse_classes)
+}
+
 impl ListFilter {
-    pub fn new(value_list: Vec<u8>, attribute_name: &String) -> ListFilter {
+    pub fn new(value_list: Vec<u8>, attribute_name: &str) -> ListFilter {
         ListFilter {
             value: value_list,
             header_attribute: attribute_name.to_string(),
@@ -17,8 +105,62 @@ impl ListFilter {
 }
 
 impl Compare for ListFilter {
+    fn attribute(&self) -> &str {
+        &self.header_attribute
+    }
+
+    fn header_value(&self, value: &MyHeader) -> String {
+        match self.header_attribute.as_str() {
+            "point_format" => value
+                .point_format
+                .to_u8()
+                .map_or(String::from("-"), |pf| pf.to_string()),
+            "has_class" => value.points.as_ref().map_or(String::from("-"), |points| {
+                let classes: Vec<String> = points.classes.keys().map(|c| c.to_string()).collect();
+                classes.join(",")
+            }),
+            _ => String::from("-"),
+        }
+    }
+
     fn compare(&self, value: &MyHeader) -> bool {
-        let pf = value.point_format.to_u8().unwrap();
-        self.value.contains(&pf)
+        match self.header_attribute.as_str() {
+            "point_format" => match value.point_format.to_u8() {
+                Ok(pf) => self.value.contains(&pf),
+                Err(_) => {
+                    warn!("Unable to filter a {}", value.point_format);
+                    false
+                }
+            },
+            "has_class" => value.points.as_ref().is_some_and(|points| {
+                self.value
+                    .iter()
+                    .any(|class| points.classes.contains_key(class))
+            }),
+            _ => false,
+        }
+    }
+}
+
+impl Display for ListFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let values: Vec<String> = self.value.iter().map(|v| v.to_string()).collect();
+        write!(f, "{} in {{{}}}", self.header_attribute, values.join(","))
     }
 }
+
+#[test]
+fn test_point_formats() {
+    assert_eq!(parse_point_formats("10"), Ok(vec![10]));
+    assert_eq!(parse_point_formats("6-10"), Ok(vec![6, 7, 8, 9, 10]));
+    assert_eq!(parse_point_formats("0..2"), Ok(vec![0, 1, 2]));
+    assert_eq!(parse_point_formats("134"), Ok(vec![6]));
+    assert!(parse_point_formats("11").is_err());
+    assert!(parse_point_formats("8-6").is_err());
+    assert_eq!(parse_point_format_list("in {6,7}"), Ok(vec![6, 7]));
+    assert_eq!(parse_point_format_list("[0..2, 1]"), Ok(vec![0, 1, 2]));
+    assert_eq!(parse_class_list("in {2,9}"), Ok(vec![2, 9]));
+    assert_eq!(parse_class_list("[3-5]"), Ok(vec![3, 4, 5]));
+    assert!(parse_class_list("256").is_err());
+    assert!(parse_class_list("5-3").is_err());
+}
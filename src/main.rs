@@ -1,91 +1,284 @@
-mod date_filter;
-mod filter;
-mod header;
-mod list_filter;
-mod numeric_filter;
-mod spatial_filter;
-mod string_filter;
-
-use chrono::NaiveDate;
-use clap::builder::Str;
-use clap::{Arg, ArgAction, ArgGroup, Command};
+mod config;
+
+use clap::error::ErrorKind;
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
 use env_logger::Builder;
 use std::default::Default;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 use wkt::TryFromWkt;
 
-use crate::filter::{Filter, LasHeaderFilter};
-use crate::list_filter::ListFilter;
-use crate::numeric_filter::NumericFilter;
-use crate::string_filter::StringFilter;
+use crate::config::Config;
+use lg::bool_filter::BoolFilter;
+use lg::coverage::Coverage;
+use lg::filter::{Compare, Filter, LasHeaderFilter, MissingPolicy, MissingValueError};
+use lg::group::GroupBy;
+use lg::list_filter::ListFilter;
+use lg::numeric_filter::NumericFilter;
+use lg::stats::Stats;
+use lg::string_filter::StringFilter;
+use lg::tile_name::{CellOrigin, TileGrid, TileNames, NAME_PREFIX};
+use lg::validate::ValidationReport;
+use lg::walk::{walk, MatchedFile, WalkOptions};
+use lg::{geometry, group, info, list_filter, numeric_filter, sort, spatial_filter};
 
-use crate::date_filter::DateFilter;
-use crate::spatial_filter::{SpatialFilter, SpatialPredicate};
 use geo::Geometry;
-use las::{Read, Reader};
+use lg::date_filter::DateFilter;
+use lg::diff::{HeaderDiff, PairBy};
+use lg::duplicates::{self, Duplicates};
+use lg::footprint::FootprintSource;
+use lg::spatial_filter::{SpatialFilter, SpatialPredicate};
 use log::{debug, LevelFilter};
-use walkdir::{DirEntry, WalkDir};
+use regex::Regex;
 
-fn file_filter(entry: DirEntry, inverse: bool, extensions: &Vec<&str>, filter: &LasHeaderFilter) {
-    let extension = entry.path().extension().unwrap().to_ascii_lowercase();
-    let extension_str = extension.to_str().unwrap();
+/// Numeric header attributes selectable from the command line: (field, long option, help).
+const NUMERIC_OPTIONS: &[(&str, &str, &str)] = &[
+    (
+        "point_data_record_length",
+        "point-record-length",
+        "Selects files according the point data record length, in bytes",
+    ),
+    (
+        "header_size",
+        "header-size",
+        "Selects files according the size of the header, in bytes",
+    ),
+    (
+        "offset_to_point_data",
+        "offset-to-point-data",
+        "Selects files according the offset to the point data, in bytes",
+    ),
+    (
+        "number_of_vlrs",
+        "vlrs-number",
+        "Selects files according the number of variable length records",
+    ),
+    (
+        "min_x",
+        "min-x",
+        "Selects files according the minimum x bound",
+    ),
+    (
+        "min_y",
+        "min-y",
+        "Selects files according the minimum y bound",
+    ),
+    (
+        "min_z",
+        "min-z",
+        "Selects files according the minimum z bound",
+    ),
+    (
+        "max_x",
+        "max-x",
+        "Selects files according the maximum x bound",
+    ),
+    (
+        "max_y",
+        "max-y",
+        "Selects files according the maximum y bound",
+    ),
+    (
+        "max_z",
+        "max-z",
+        "Selects files according the maximum z bound",
+    ),
+    (
+        "scale_x",
+        "scale-x",
+        "Selects files according the x scale factor",
+    ),
+    (
+        "scale_y",
+        "scale-y",
+        "Selects files according the y scale factor",
+    ),
+    (
+        "scale_z",
+        "scale-z",
+        "Selects files according the z scale factor",
+    ),
+    (
+        "offset_x",
+        "offset-x",
+        "Selects files according the x offset",
+    ),
+    (
+        "offset_y",
+        "offset-y",
+        "Selects files according the y offset",
+    ),
+    (
+        "offset_z",
+        "offset-z",
+        "Selects files according the z offset",
+    ),
+    (
+        "day_of_year",
+        "day-of-year",
+        "Selects files according the file creation day of year",
+    ),
+    (
+        "year",
+        "year",
+        "Selects files according the file creation year",
+    ),
+    (
+        "epsg",
+        "epsg",
+        "Selects files according the EPSG code of their CRS",
+    ),
+    (
+        "copc_spacing",
+        "copc-spacing",
+        "Selects COPC files according the spacing of their octree root",
+    ),
+    (
+        "extra_bytes",
+        "extra-bytes",
+        "Selects files according the number of extra bytes per point (eg. \">0\")",
+    ),
+];
 
-    if extensions.contains(&extension_str) {
-        let path = entry.path();
-        let reader = Reader::from_path(path);
+/// Point format properties selectable from the command line: (field, long option, help).
+const BOOL_OPTIONS: &[(&str, &str, &str)] = &[
+    (
+        "has_gps_time",
+        "has-gps-time",
+        "Selects files whose points have a GPS time",
+    ),
+    (
+        "has_color",
+        "has-color",
+        "Selects files whose points have RGB colors",
+    ),
+    (
+        "has_nir",
+        "has-nir",
+        "Selects files whose points have a near infrared channel",
+    ),
+    (
+        "has_waveform",
+        "has-waveform",
+        "Selects files whose points have waveform packets",
+    ),
+    (
+        "extended",
+        "extended",
+        "Selects files with an extended point format (formats 6 to 10)",
+    ),
+];
 
-        match reader {
-            Ok(reader) => {
-                let header = header::MyHeader::new(reader.header());
+/// Per attribute overrides of the missing values policy: (id, long option, attributes).
+const MISSING_OPTIONS: &[(&str, &str, &[&str])] = &[
+    (
+        "missing_date",
+        "missing-date",
+        &["date", "day_of_year", "year"],
+    ),
+    ("missing_crs", "missing-crs", &["epsg"]),
+    ("missing_copc", "missing-copc", &["copc_spacing"]),
+];
 
-                match inverse ^ filter.filter(&header) {
-                    true => {
-                        println!("{}", entry.path().display())
-                    }
-                    false => {}
-                }
-            }
-            Err(error) => {
-                let path_string = path.display();
-                eprintln!("Impossible to read {path_string}: {error}")
-            }
-        }
-    };
+const POINTS_NUMBER_HELP: &str = "Selects files according the number of points.\n\n\
+    Numeric filters accept the following syntaxes :\n\
+      - \"<op><value>\" with op in \"=\", \"!=\", \"<=\", \">=\", \"<\" and \">\" (eg. \">1000\")\n\
+      - \"<min>..<max>\" for an inclusive range, a bound can be omitted (eg. \"10k..1M\", \"..-5\")\n\
+      - \"in {<values>}\" for a set of values (eg. \"in {1,2,3}\")\n\n\
+    Values accept a sign, an exponent (eg. \"1e6\") and a \"k\", \"M\" or \"G\" suffix.";
+
+fn numeric_args() -> Vec<Arg> {
+    NUMERIC_OPTIONS
+        .iter()
+        .map(|(field, long, help)| {
+            Arg::new(*field)
+                .long(*long)
+                .value_parser(numeric_filter::check_syntax)
+                .allow_hyphen_values(true)
+                .help(*help)
+        })
+        .collect()
+}
+
+fn bool_args() -> Vec<Arg> {
+    BOOL_OPTIONS
+        .iter()
+        .map(|(field, long, help)| {
+            Arg::new(*field)
+                .long(*long)
+                .value_parser(clap::value_parser!(bool))
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("true")
+                .help(format!("{help} (\"--{long}=false\" to invert)"))
+        })
+        .collect()
 }
 
+fn missing_args() -> Vec<Arg> {
+    MISSING_OPTIONS
+        .iter()
+        .map(|(id, long, attributes)| {
+            Arg::new(*id)
+                .long(*long)
+                .value_parser(clap::value_parser!(MissingPolicy))
+                .help(format!(
+                    "Overrides --missing for the {} attribute",
+                    attributes[0]
+                ))
+        })
+        .collect()
+}
+
+/// Walks a folder and filters its files, returns the number of errors.
 // Add ignore dir
 fn folder_walk(
+    input: usize,
     folder: &str,
-    recursive: bool,
-    f_links: bool,
-    inverse: bool,
-    // ignore_dirs: &Vec<&str>,
-    extensions: &Vec<&str>,
+    options: &WalkOptions,
     filter: &LasHeaderFilter,
-) {
-    let folder_iterator = WalkDir::new(folder)
-        .max_depth(if recursive { usize::MAX } else { 1 })
-        .follow_links(f_links);
-
-    for entry in folder_iterator {
-        if let Ok(entry) = entry {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() {
-                    file_filter(entry, inverse, extensions, filter);
-                }
+    on_file: &mut dyn FnMut(MatchedFile, bool),
+) -> usize {
+    let mut errors = 0;
+    for result in walk(folder, options, filter).input(input) {
+        match result {
+            Ok((file, selected)) => on_file(file, selected),
+            Err(error) => {
+                eprintln!("{error}");
+                errors += 1;
             }
-        };
+        }
     }
+    errors
 }
 
-fn main() {
-    let args = Command::new("lg")
-        .version("0.2.0")
-        .author("Mattia B. <mattia.bunel@ign.fr>")
-        .about(
-            "lg (LasGrep) is a tool to filter asprs's las and laz files, with the informations \
-        contained in the file header.",
+fn scan_points_arg() -> Arg {
+    Arg::new("scan_points")
+        .long("scan-points")
+        .value_parser(clap::value_parser!(u64).range(1..))
+        .num_args(0..=1)
+        .require_equals(true)
+        .default_missing_value("1")
+        .help("Reads the points (one every N with \"--scan-points=N\") to filter on them")
+        .long_help(
+            "Reads the points of the files, or one point every N points with \
+            \"--scan-points=N\", to filter them on their content: --has-class, --gps-time, \
+            --intensity, and the scanned_points, scanned_min_x... query attributes.",
         )
+}
+
+fn format_arg() -> Arg {
+    Arg::new("format")
+        .long("format")
+        .value_parser(["text", "csv", "json"])
+        .default_value("text")
+        .help("Output format of the reports")
+}
+
+/// Adds the input, filtering and walking arguments shared by the listing and the reports.
+fn filter_args(command: Command) -> Command {
+    command
         // Arguments Needed
         .arg(
             Arg::new("input")
@@ -93,12 +286,18 @@ fn main() {
                 .action(ArgAction::Append)
                 .help("help string"),
         )
-        // Options
         .arg(
-            Arg::new("verbose")
-                .short('v')
-                .action(clap::ArgAction::Count)
-                .help("Non implemented yet"),
+            Arg::new("query")
+                .long("query")
+                .value_parser(|query: &str| {
+                    query.parse::<LasHeaderFilter>().map(|_| query.to_string())
+                })
+                .help("Selects files according to a query, as printed by --debug")
+                .long_help(
+                    "Selects files according to a query, as printed by --debug \
+                    (eg. 'number_of_points >1000 and date 2023'). The other filtering options \
+                    are added to the query.",
+                ),
         )
         .next_help_heading("Find name")
         .arg(
@@ -106,7 +305,7 @@ fn main() {
                 //.short('t')
                 .long("las-version")
                 .long("las-version")
-                .value_parser(clap::value_parser!(String))
+                .value_parser(string_condition("version"))
                 .help("Selects files according to the version of the las standard.")
                 .long_help(
                     "Selects files according to the version of the las standard. The \
@@ -125,37 +324,50 @@ fn main() {
         .arg(
             Arg::new("generating_software")
                 .long("generating-software")
-                .value_parser(clap::value_parser!(String))
+                .value_parser(string_condition("generating_software"))
                 .help("Selects files according the generating software"),
         )
         .arg(
             Arg::new("system_identifier")
                 .long("system-identifier")
-                .value_parser(clap::value_parser!(String))
+                .value_parser(string_condition("system_identifier"))
                 .help("Selects files according the system identifier"),
         )
         .arg(
             Arg::new("date")
                 .long("date")
                 .value_parser(clap::value_parser!(String))
-                .help("Not implemented now"),
+                .allow_hyphen_values(true)
+                .help("Selects files according the creation date (eg. \">=2023-01-01\", \"2023\")")
+                .long_help(
+                    "Selects files according the creation date of the file.\n\n\
+                    The following syntaxes are supported :\n\
+                      - \"<op><date>\" with op in \"=\", \"!=\", \"<=\", \">=\", \"<\" and \">\"\n\
+                      - \"<start>..<end>\" for an inclusive range, a bound can be omitted\n\n\
+                    Dates can be written with --date-format (ISO-8601 by default, eg. \"2023-06-30\"), \
+                    as a day of year (eg. \"2023-181\"), as a year (eg. \"2023\") or relatively to \
+                    today (eg. \"today\", \"-30d\", \"-2w\", \"-6m\", \"-1y\").",
+                ),
         )
         .arg(
             Arg::new("date-format")
                 .long("date-format")
                 .value_parser(clap::value_parser!(String))
-                .default_value("%d/%m/%Y")
+                .default_value("%Y-%m-%d")
                 .requires("date")
-                .help("Not implemented now"),
+                .help("Format of the dates given to --date (eg. \"%d/%m/%Y\")"),
         )
         .arg(
             Arg::new("guid")
                 .long("guid")
+                .value_parser(string_condition("guid"))
                 .help("Selects files according the guid"),
         )
         .arg(
             Arg::new("file_source_id")
                 .long("file-source-id")
+                .value_parser(numeric_filter::check_syntax)
+                .allow_hyphen_values(true)
                 .help("Selects files according the file source id"),
         )
         .arg(
@@ -167,27 +379,61 @@ fn main() {
         .arg(
             Arg::new("point_format")
                 .long("point-format")
-                .value_parser(clap::value_parser!(u8).range(0..10))
+                .value_parser(list_filter::parse_point_formats)
                 .value_delimiter(',')
-                .help("Selects files according the format of points (eg. \"1\", \"2,6,10\")"),
+                .help(
+                    "Selects files according the format of points (eg. \"1\", \"2,6,10\", \
+                    \"0-3,6-10\")",
+                ),
         )
         .arg(
             Arg::new("transform")
                 .long("transform")
                 .help("Not implemented now"),
         )
-        .arg(Arg::new("points_number").long("points-number").help(
-            "Selects files according the number of points (eg. \">1000\"). \
-                Allowed operators : \"=\", \"!=\", \"<=\", \">=\", \"<\" and \">\".",
-        ))
+        .arg(
+            Arg::new("points_number")
+                .long("points-number")
+                .value_parser(numeric_filter::check_syntax)
+                .allow_hyphen_values(true)
+                .help("Selects files according the number of points (eg. \">1000\", \">10M\")")
+                .long_help(POINTS_NUMBER_HELP),
+        )
+        .next_help_heading("Numeric filters")
+        .args(numeric_args())
+        .next_help_heading("Point format properties")
+        .args(bool_args())
         // Spatial filters
         .next_help_heading("Spatial filters")
         .arg(
             Arg::new("wkt")
                 .long("wkt")
-                .value_parser(clap::value_parser!(String))
+                .value_parser(|wkt: &str| {
+                    Geometry::<f64>::try_from_wkt_str(wkt).map_err(|error| error.to_string())
+                })
                 .help("Todo"),
         )
+        .arg(
+            Arg::new("geometry_file")
+                .long("geometry-file")
+                .value_parser(geometry::read_geometry_file)
+                .help("Reads the geometry of the spatial filters from a GeoJSON or a WKT file"),
+        )
+        .arg(
+            Arg::new("footprint")
+                .long("footprint")
+                .value_parser(["bbox", "hull", "sidecar"])
+                .default_value("bbox")
+                .requires("footprint_readers")
+                .requires_if("hull", "scan_points")
+                .help("Footprint of the files compared to the geometry")
+                .long_help(
+                    "Footprint of the files compared to the geometry, or to each other with \
+                    --overlap: the box of the header bounds, the convex hull of the points \
+                    read with --scan-points, or the polygon of a GeoJSON or WKT file next to \
+                    each tile, with the same name (tile.geojson, tile.json or tile.wkt)",
+                ),
+        )
         .arg(
             Arg::new("intersects")
                 .long("intersects")
@@ -215,8 +461,186 @@ fn main() {
         .arg(
             Arg::new("distance")
                 .long("distance")
-                .help("Todo (Distance in CRS's units)"),
+                .value_parser(parse_distance)
+                .help("Selects files whose footprint is at most this distance from the geometry")
+                .long_help(
+                    "Selects files whose footprint is at most this distance (in CRS units) from \
+                    the closest point of the geometry, eg. tiles along a road",
+                ),
+        )
+        .arg(
+            Arg::new("centroid_distance")
+                .long("centroid-distance")
+                .value_parser(parse_distance)
+                .help(
+                    "Selects files whose footprint centroid is at most this distance from the \
+                    geometry centroid",
+                ),
+        )
+        .arg(
+            Arg::new("buffer")
+                .long("buffer")
+                .value_parser(parse_positive)
+                .requires("spatial_readers")
+                .help("Dilates the geometry by this distance (in CRS units) before filtering"),
+        )
+        .arg(
+            Arg::new("disjoint")
+                .long("disjoint")
+                .action(ArgAction::SetTrue)
+                .help("Selects files whose footprint doesn't share any point with the geometry"),
+        )
+        .arg(
+            Arg::new("touches")
+                .long("touches")
+                .action(ArgAction::SetTrue)
+                .help("Selects files whose footprint only shares boundary points with the geometry"),
+        )
+        .arg(
+            Arg::new("overlaps")
+                .long("overlaps")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Selects files whose footprint partly overlaps the geometry, of the same \
+                    dimension",
+                ),
+        )
+        .arg(
+            Arg::new("crosses")
+                .long("crosses")
+                .action(ArgAction::SetTrue)
+                .help("Selects files whose footprint crosses the geometry (eg. a line)"),
+        )
+        .arg(
+            Arg::new("covers")
+                .long("covers")
+                .action(ArgAction::SetTrue)
+                .help("Selects files whose footprint has no point of the geometry outside of it"),
+        )
+        .arg(
+            Arg::new("covered_by")
+                .long("covered-by")
+                .action(ArgAction::SetTrue)
+                .help("Selects files whose footprint has no point outside of the geometry"),
+        )
+        .arg(
+            Arg::new("relate")
+                .long("relate")
+                .value_parser(spatial_filter::check_relate_pattern)
+                .help(
+                    "Selects files whose footprint relation to the geometry matches a DE-9IM \
+                    pattern (eg. \"T*F**F***\")",
+                ),
+        )
+        .next_help_heading("Points")
+        .arg(scan_points_arg())
+        .arg(
+            Arg::new("has_class")
+                .long("has-class")
+                .value_parser(list_filter::parse_classes)
+                .value_delimiter(',')
+                .requires("scan_points")
+                .help(
+                    "Selects files with points of one of the classifications (eg. \"9\", \
+                    \"2,9\", \"3-5\")",
+                ),
+        )
+        .arg(
+            Arg::new("gps_time")
+                .long("gps-time")
+                .value_parser(numeric_filter::check_syntax)
+                .allow_hyphen_values(true)
+                .requires("scan_points")
+                .help("Selects files with points whose GPS time may match (eg. \"1e9..1.1e9\")"),
+        )
+        .arg(
+            Arg::new("intensity")
+                .long("intensity")
+                .value_parser(numeric_filter::check_syntax)
+                .requires("scan_points")
+                .help("Selects files with points whose intensity may match (eg. \">1000\")"),
         )
+        .next_help_heading("Validation")
+        .arg(
+            Arg::new("valid")
+                .long("valid")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("invalid")
+                .help("Selects files whose header is consistent (see the validate subcommand)"),
+        )
+        .arg(
+            Arg::new("invalid")
+                .long("invalid")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Selects files whose header is inconsistent with the specification or \
+                    with the file (see the validate subcommand)",
+                ),
+        )
+        .next_help_heading("Tile names")
+        .arg(
+            Arg::new("name_pattern")
+                .long("name-pattern")
+                .value_parser(|pattern: &str| Regex::new(pattern).map_err(|e| e.to_string()))
+                .help(
+                    "Captures attributes from the file names with the named groups of a regex \
+                    (eg. '_(?P<x>\\d{4})_(?P<y>\\d{4})_'), filtered as name.<group>",
+                ),
+        )
+        .arg(
+            Arg::new("name_field")
+                .long("name-field")
+                .action(ArgAction::Append)
+                .value_parser(parse_name_field)
+                .requires("name_pattern")
+                .help(
+                    "Selects files according to a group of the name pattern, with a numeric \
+                    condition or a regex (eg. \"x>=650\", \"x=650..700\", \"zone=FXX\")",
+                ),
+        )
+        .arg(
+            Arg::new("cell_size")
+                .long("cell-size")
+                .value_parser(parse_positive)
+                .requires("name_pattern")
+                .help(
+                    "Size of the grid cells, in CRS units: validation checks that the bounds \
+                    fall inside the cell of the x and y groups of the name",
+                ),
+        )
+        .arg(
+            Arg::new("name_scale")
+                .long("name-scale")
+                .value_parser(parse_positive)
+                .requires("cell_size")
+                .help(
+                    "Multiplier from the x and y of the names to CRS units (eg. 1000 for \
+                    kilometers), the cell size by default",
+                ),
+        )
+        .arg(
+            Arg::new("cell_origin")
+                .long("cell-origin")
+                .value_parser(["lower-left", "upper-left"])
+                .default_value("lower-left")
+                .help("Corner of the cells given by the x and y of the names"),
+        )
+        .next_help_heading("Missing values")
+        .arg(
+            Arg::new("missing")
+                .long("missing")
+                .value_parser(clap::value_parser!(MissingPolicy))
+                .default_value("match")
+                .help("Behavior when a filtered attribute is missing from the header")
+                .long_help(
+                    "Behavior when a filtered attribute is missing from the header (date, CRS, \
+                    COPC info...).\n\n\
+                      - \"match\" : the filter is ignored, the file passes it\n\
+                      - \"skip\" : the file is rejected\n\
+                      - \"error\" : the file is rejected and reported on the error output",
+                ),
+        )
+        .args(missing_args())
         .next_help_heading("Find name 2")
         .arg(
             Arg::new("extensions")
@@ -255,7 +679,6 @@ fn main() {
         )
         .arg(
             Arg::new("canonicalize")
-                .short('c')
                 .long("canonicalize")
                 .action(ArgAction::SetTrue)
                 .help("Not implemented now"),
@@ -267,6 +690,34 @@ fn main() {
                 .action(ArgAction::SetTrue)
                 .help("Print the parameters and quit (for debug purposes)"),
         )
+        .next_help_heading("Sorting")
+        .arg(
+            Arg::new("sort_by")
+                .long("sort-by")
+                .value_parser(sort::SORT_KEYS)
+                .requires_if("distance", "spatial_readers")
+                .help("Sorts the selected files (\"distance\" is computed to the spatial filter geometry)"),
+        )
+        .arg(
+            Arg::new("reverse")
+                .long("reverse")
+                .action(ArgAction::SetTrue)
+                .requires("sort_by")
+                .help("Sorts in descending order"),
+        )
+        .arg(
+            Arg::new("limit")
+                .long("limit")
+                .value_parser(clap::value_parser!(usize))
+                .help("Keeps only the N first selected files"),
+        )
+        .arg(
+            Arg::new("first")
+                .long("first")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("limit")
+                .help("Keeps only the first selected file (same as --limit 1)"),
+        )
         //.group(ArgGroup::new("Test").arg("transform").arg("extensions"))
         // Groups
         .group(
@@ -277,108 +728,210 @@ fn main() {
                 .arg("centroid_within")
                 .arg("contains")
                 .arg("distance")
+                .arg("centroid_distance")
+                .args([
+                    "disjoint",
+                    "touches",
+                    "overlaps",
+                    "crosses",
+                    "covers",
+                    "covered_by",
+                    "relate",
+                ])
                 .requires("spatial_readers")
                 .multiple(false),
         )
         .group(
             ArgGroup::new("spatial_readers")
-                .arg("wkt")
+                .args(["wkt", "geometry_file"])
                 .requires("spatial_predicates"),
         )
-        .get_matches();
+        .group(
+            ArgGroup::new("footprint_readers")
+                .args(["wkt", "geometry_file"])
+                .multiple(true),
+        )
+}
 
-    let mut log_builder = Builder::new();
-    let verbose_count = args.get_count("verbose");
-    let default_log_level = 1; // Error
-    let log_level = default_log_level + verbose_count;
-    match log_level {
-        0..=5 => {
-            let level_filter = LevelFilter::iter().nth(log_level as usize).unwrap();
-            log_builder.filter_level(level_filter);
-        }
-        _ => {
-            // If too much verbose, set to max
-            log_builder.filter_level(LevelFilter::max());
-        }
+/// Checks the condition of a string filter on `attribute` (a regex, or a semver requirement).
+fn string_condition(attribute: &'static str) -> impl Fn(&str) -> Result<String, String> + Clone {
+    move |input| StringFilter::new(input, attribute).map(|_| input.to_string())
+}
+
+/// Parses a strictly positive number.
+fn parse_positive(input: &str) -> Result<f64, String> {
+    match numeric_filter::parse_number(input)? {
+        value if value > 0.0 => Ok(value),
+        value => Err(format!("{value} is not positive")),
     }
-    log_builder.init();
+}
+
+/// Parses a positive or zero distance.
+fn parse_distance(input: &str) -> Result<f64, String> {
+    match numeric_filter::parse_number(input)? {
+        value if value >= 0.0 => Ok(value),
+        value => Err(format!("{value} is not a distance")),
+    }
+}
 
+/// Checks the --date condition against --date-format.
+fn check_date(args: &ArgMatches) -> Result<(), String> {
+    match (
+        args.try_get_one::<String>("date"),
+        args.try_get_one::<String>("date-format"),
+    ) {
+        (Ok(Some(date)), Ok(Some(date_format))) => DateFilter::new(date, date_format, "date")
+            .map(|_| ())
+            .map_err(|error| format!("invalid value '{date}' for '--date <date>': {error}")),
+        _ => Ok(()),
+    }
+}
+
+/// Parses a --name-field condition, as (group, condition): "x>=650", "x=650..700" or "zone=FXX".
+fn parse_name_field(input: &str) -> Result<(String, String), String> {
+    let end = input
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(input.len());
+    let (field, condition) = input.split_at(end);
+    let condition = condition.strip_prefix('=').unwrap_or(condition);
+    if field.is_empty() || condition.is_empty() {
+        return Err(format!(
+            "expected a group and a condition, eg. \"x>=650\", not \"{input}\""
+        ));
+    }
+    if numeric_filter::parse_condition(condition).is_err() {
+        Regex::new(condition).map_err(|error| error.to_string())?;
+    }
+    Ok((field.to_string(), condition.to_string()))
+}
+
+/// Number of files kept by --limit or --first.
+fn limit(args: &ArgMatches) -> Option<usize> {
+    match args.get_flag("first") {
+        true => Some(1),
+        false => args.get_one::<usize>("limit").copied(),
+    }
+}
+
+/// Pattern of the file names, and the grid it is checked against.
+fn tile_names(args: &ArgMatches) -> Option<TileNames> {
+    let pattern = args.get_one::<Regex>("name_pattern")?.clone();
+    let grid = args.get_one::<f64>("cell_size").map(|&size| TileGrid {
+        size,
+        scale: args.get_one::<f64>("name_scale").copied().unwrap_or(size),
+        origin: args
+            .get_one::<String>("cell_origin")
+            .unwrap()
+            .parse::<CellOrigin>()
+            .unwrap(),
+    });
+    Some(TileNames { pattern, grid })
+}
+
+fn build_filter(args: &ArgMatches) -> Result<LasHeaderFilter, String> {
     // Creation of filter object
     let mut filter = LasHeaderFilter {
         ..Default::default()
     };
 
+    filter.missing_policy = *args.get_one::<MissingPolicy>("missing").unwrap();
+    for (id, _, attributes) in MISSING_OPTIONS {
+        if let Some(policy) = args.get_one::<MissingPolicy>(id) {
+            for attribute in attributes.iter() {
+                filter
+                    .missing_policy_overrides
+                    .insert(attribute.to_string(), *policy);
+            }
+        }
+    }
+
     // Numeric Type
-    if let Some(point_number) = args.get_one("points_number") {
+    if let Some(point_number) = args.get_one::<String>("points_number") {
         filter.number_of_points_filter = Some(Filter::FNumeric(NumericFilter::new(
             point_number,
-            &String::from("number_of_points"),
-        )));
+            "number_of_points",
+        )?));
     }
 
-    if let Some(file_source_id) = args.get_one("file_source_id") {
+    if let Some(file_source_id) = args.get_one::<String>("file_source_id") {
         filter.file_source_id_filter = Some(Filter::FNumeric(NumericFilter::new(
             file_source_id,
-            &String::from("file_source_id"),
-        )));
+            "file_source_id",
+        )?));
     }
 
-    if let Some(point_format) = args.get_many::<u8>("point_format") {
-        filter.point_format_filter = Some(Filter::FList(ListFilter::new(
-            point_format.map(|x| x.clone()).collect::<Vec<_>>(),
-            &String::from("file_source_id"),
-        )));
+    for (field, _, _) in NUMERIC_OPTIONS {
+        if let Some(value) = args.get_one::<String>(field) {
+            filter
+                .attribute_filters
+                .push(Filter::FNumeric(NumericFilter::new(value, field)?));
+        }
     }
 
-    // String Type
-    if let Some(gps_time_type) = args.get_one("gps_time_type") {
-        filter.gps_time_type_filter = Some(Filter::FString(StringFilter::new(
-            gps_time_type,
-            &String::from("gps_time_type"),
-        )));
+    for (field, _, _) in BOOL_OPTIONS {
+        if let Some(value) = args.get_one::<bool>(field) {
+            filter
+                .attribute_filters
+                .push(Filter::FBool(BoolFilter::new(*value, field)));
+        }
     }
 
-    if let Some(las_version) = args.get_one("las_version") {
-        filter.version_filter = Some(Filter::FString(StringFilter::new(
-            las_version,
-            &String::from("las_version"),
+    if args.get_flag("valid") || args.get_flag("invalid") {
+        filter.attribute_filters.push(Filter::FBool(BoolFilter::new(
+            args.get_flag("valid"),
+            "valid",
         )));
     }
 
-    if let Some(date) = args.get_one::<String>("date") {
-        let date_format = args.get_one::<String>("date-format").unwrap();
-
-        filter.date_filter = Some(Filter::FDate(DateFilter::new(
-            date,
-            date_format,
-            &String::from("date"),
-        )))
+    if let Some(classes) = args.get_many::<Vec<u8>>("has_class") {
+        filter.attribute_filters.push(Filter::FList(ListFilter::new(
+            classes.flatten().copied().collect::<Vec<_>>(),
+            "has_class",
+        )));
     }
 
-    if let Some(guid) = args.get_one("guid") {
-        filter.guid_filter = Some(Filter::FString(StringFilter::new(
-            guid,
-            &String::from("guid"),
-        )))
+    for field in ["gps_time", "intensity"] {
+        if let Some(value) = args.get_one::<String>(field) {
+            filter
+                .attribute_filters
+                .push(Filter::FNumeric(NumericFilter::new(value, field)?));
+        }
     }
 
-    if let Some(generating_software) = args.get_one("generating_software") {
-        filter.generating_software_filter = Some(Filter::FString(StringFilter::new(
-            generating_software,
-            &String::from("generating_software"),
-        )))
+    if let Some(point_format) = args.get_many::<Vec<u8>>("point_format") {
+        filter.point_format_filter = Some(Filter::FList(ListFilter::new(
+            point_format.flatten().copied().collect::<Vec<_>>(),
+            &String::from("point_format"),
+        )));
     }
 
-    if let Some(system_identifier) = args.get_one("system_identifier") {
-        filter.system_identifier_filter = Some(Filter::FString(StringFilter::new(
-            system_identifier,
-            &String::from("system_identifier"),
-        )))
+    // String Type
+    for (arg, attribute) in [
+        ("gps_time_type", "gps_time_type"),
+        ("las_version", "version"),
+        ("guid", "guid"),
+        ("generating_software", "generating_software"),
+        ("system_identifier", "system_identifier"),
+    ] {
+        if let Some(value) = args.get_one::<String>(arg) {
+            filter.add(Filter::FString(StringFilter::new(value, attribute)?));
+        }
     }
 
-    if let Some(wkt_string) = args.get_one::<String>("wkt") {
-        let geometry: Geometry<f64> = Geometry::try_from_wkt_str(wkt_string).unwrap();
+    if let Some(date) = args.get_one::<String>("date") {
+        let date_format = args.get_one::<String>("date-format").unwrap();
 
+        filter.date_filter = Some(Filter::FDate(DateFilter::new(date, date_format, "date")?))
+    }
+
+    let geometry = args
+        .get_one::<Geometry>("wkt")
+        .or(args.get_one::<Geometry>("geometry_file"))
+        .cloned();
+    if let Some(mut geometry) = geometry {
+        if let Some(&distance) = args.get_one::<f64>("buffer") {
+            geometry = Geometry::MultiPolygon(geometry::buffer(&geometry, distance));
+        }
         let mut predicate: SpatialPredicate = SpatialPredicate::Intersects;
 
         if args.get_flag("intersects") {
@@ -389,15 +942,556 @@ fn main() {
             predicate = SpatialPredicate::Contains
         } else if args.get_flag("centroid_within") {
             predicate = SpatialPredicate::CentroidWithin
-        } else {
-            if let Some(distance) = args.get_one::<String>("distance") {
-                predicate = SpatialPredicate::CentroidDistance {
-                    dist: distance.parse::<f64>().unwrap(),
+        } else if args.get_flag("disjoint") {
+            predicate = SpatialPredicate::Disjoint
+        } else if args.get_flag("touches") {
+            predicate = SpatialPredicate::Touches
+        } else if args.get_flag("overlaps") {
+            predicate = SpatialPredicate::Overlaps
+        } else if args.get_flag("crosses") {
+            predicate = SpatialPredicate::Crosses
+        } else if args.get_flag("covers") {
+            predicate = SpatialPredicate::Covers
+        } else if args.get_flag("covered_by") {
+            predicate = SpatialPredicate::CoveredBy
+        } else if let Some(pattern) = args.get_one::<String>("relate") {
+            predicate = SpatialPredicate::Relate {
+                pattern: pattern.clone(),
+            }
+        } else if let Some(&dist) = args.get_one::<f64>("distance") {
+            predicate = SpatialPredicate::Distance { dist }
+        } else if let Some(&dist) = args.get_one::<f64>("centroid_distance") {
+            predicate = SpatialPredicate::CentroidDistance { dist }
+        }
+        let footprint = args.get_one::<String>("footprint").unwrap().parse()?;
+        filter.geom_filter = Some(Filter::FSpatial(SpatialFilter::new(
+            geometry, predicate, footprint,
+        )?))
+    }
+
+    // Groups of the name pattern are numbers when compared to a numeric condition
+    for (field, condition) in args
+        .get_many::<(String, String)>("name_field")
+        .unwrap_or_default()
+    {
+        let attribute = format!("{NAME_PREFIX}{field}");
+        filter.add(match numeric_filter::parse_condition(condition) {
+            Ok(_) => Filter::FNumeric(NumericFilter::new(condition, &attribute)?),
+            Err(_) => Filter::FString(StringFilter::new(condition, &attribute)?),
+        });
+    }
+
+    // The query clauses are added to the options
+    if let Some(query) = args.get_one::<String>("query") {
+        for query_filter in query.parse::<LasHeaderFilter>()?.into_filters() {
+            filter.add(query_filter);
+        }
+    }
+
+    Ok(filter)
+}
+
+fn main() -> ExitCode {
+    let command = filter_args(Command::new("lg"))
+        .version("0.2.0")
+        .author("Mattia B. <mattia.bunel@ign.fr>")
+        .about(
+            "lg (LasGrep) is a tool to filter asprs's las and laz files, with the informations \
+        contained in the file header.",
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .next_help_heading("Reports")
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .action(ArgAction::SetTrue)
+                .help("Prints statistics about the selected files instead of listing them"),
+        )
+        .arg(
+            Arg::new("group_by")
+                .long("group-by")
+                .value_delimiter(',')
+                .value_parser(group::check_attribute)
+                .conflicts_with("stats")
+                .help(
+                    "Prints the number of files and points by distinct values of header \
+                    attributes (eg. \"generating_software,version\")",
+                ),
+        )
+        .arg(format_arg())
+        .arg(
+            Arg::new("count")
+                .short('c')
+                .long("count")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["stats", "group_by"])
+                .help("Prints the number of selected files instead of listing them"),
+        )
+        .arg(
+            Arg::new("per_input")
+                .long("per-input")
+                .action(ArgAction::SetTrue)
+                .requires("count")
+                .help("Prints the number of selected files by input (with --count)"),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["stats", "group_by", "count"])
+                .help(
+                    "Prints nothing, the exit status is 0 if files are selected, 1 if none is \
+                    and 2 on errors",
+                ),
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["stats", "group_by", "count", "quiet"])
+                .help(
+                    "Prints each file with its selection, and every filter with the header \
+                    value it compares and its verdict",
+                ),
+        )
+        .arg(
+            Arg::new("duplicates")
+                .long("duplicates")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["stats", "group_by", "count", "quiet", "explain"])
+                .help(
+                    "Prints the pairs of selected files sharing a GUID or a header, or whose \
+                    footprints overlap (with --overlap)",
+                ),
+        )
+        .arg(
+            Arg::new("overlap")
+                .long("overlap")
+                .requires("duplicates")
+                .value_parser(|input: &str| {
+                    duplicates::parse_overlap(input).map(|_| input.to_string())
+                })
+                .help(
+                    "Reports the files whose footprints overlap, in percent of the smallest \
+                    one (eg. \">10%\")",
+                ),
+        )
+        .mut_group("footprint_readers", |group| group.arg("overlap"))
+        .subcommand(
+            filter_args(Command::new("stats"))
+                .about("Prints statistics about the selected files (same as --stats)")
+                .next_help_heading("Reports")
+                .arg(format_arg()),
+        )
+        .subcommand(
+            filter_args(Command::new("validate"))
+                .about(
+                    "Checks the headers of the selected files against the LAS specification \
+                    and against their file (point count, bounds, legacy counts, point format, \
+                    date, and the grid cell of the name with --cell-size)",
+                )
+                .next_help_heading("Reports")
+                .arg(format_arg()),
+        )
+        .subcommand(
+            filter_args(Command::new("diff"))
+                .about(
+                    "Compares the headers of two deliveries: lists the added, removed and \
+                    modified files, with the header fields which differ",
+                )
+                .mut_arg("input", |arg| {
+                    arg.action(ArgAction::Set)
+                        .num_args(2)
+                        .value_names(["OLD", "NEW"])
+                        .help("Folders of the old and the new delivery")
+                })
+                .mut_arg("limit", |arg| {
+                    arg.help("Keeps only the N first differences")
+                })
+                .mut_arg("first", |arg| {
+                    arg.help("Keeps only the first difference (same as --limit 1)")
+                })
+                .next_help_heading("Reports")
+                .arg(
+                    Arg::new("by")
+                        .long("by")
+                        .value_parser(["name", "guid"])
+                        .default_value("name")
+                        .help(
+                            "Pairs the files by path relative to their folder, or by GUID (files \
+                            without GUID being paired by path)",
+                        ),
+                )
+                .arg(format_arg().value_parser(["text", "json"])),
+        )
+        .subcommand(
+            filter_args(Command::new("coverage"))
+                .about(
+                    "Prints the share of a reference area covered by the footprints of the \
+                    selected files, and the gaps",
+                )
+                .mut_arg("geometry_file", |arg| {
+                    arg.required(true)
+                        .help("GeoJSON or WKT file of the reference area")
+                })
+                // The files intersecting the area are selected without spatial predicate
+                .mut_group("spatial_readers", |group| {
+                    ArgGroup::new(group.get_id().clone()).args(["wkt", "geometry_file"])
+                })
+                .next_help_heading("Reports")
+                .arg(format_arg().value_parser(["text", "json", "geojson"])),
+        )
+        .subcommand(
+            Command::new("info")
+                .about("Prints the full header of las and laz files")
+                .arg(
+                    Arg::new("files")
+                        .required(true)
+                        .action(ArgAction::Append)
+                        .help("Files to describe"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["text", "json"])
+                        .default_value("text")
+                        .help("Output format"),
+                )
+                .arg(scan_points_arg()),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .global(true)
+                .action(clap::ArgAction::Count)
+                .help("Non implemented yet"),
+        )
+        .arg(
+            Arg::new("no_config")
+                .long("no-config")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Doesn't read the configuration files (~/.config/lg/config.toml and \
+                    .lg.toml), neither their defaults nor their named queries",
+                ),
+        );
+
+    // The named queries and the defaults of the configuration files are command line options
+    let argv = std::env::args_os().collect::<Vec<_>>();
+    let config = match argv.iter().any(|arg| arg == "--no-config") {
+        true => Ok(Config::default()),
+        false => Config::load(),
+    };
+    let args = match config.and_then(|config| config.expand_args(argv, &command)) {
+        Ok(args) => command.clone().get_matches_from(args),
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::from(2);
+        }
+    };
+
+    // The dates are parsed with --date-format, so they are checked once all options are known
+    let mut command = command;
+    command.build();
+    let (mut command, matches) = match args.subcommand() {
+        Some((name, matches)) => (command.find_subcommand(name).unwrap().clone(), matches),
+        None => (command, &args),
+    };
+    if let Err(error) = check_date(matches) {
+        command.error(ErrorKind::ValueValidation, error).exit();
+    }
+
+    let mut log_builder = Builder::new();
+    let verbose_count = args.get_count("verbose");
+    let default_log_level = 1; // Error
+    let log_level = default_log_level + verbose_count;
+    match log_level {
+        0..=5 => {
+            let level_filter = LevelFilter::iter().nth(log_level as usize).unwrap();
+            log_builder.filter_level(level_filter);
+        }
+        _ => {
+            // If too much verbose, set to max
+            log_builder.filter_level(LevelFilter::max());
+        }
+    }
+    log_builder.init();
+
+    if let Some(("info", info_args)) = args.subcommand() {
+        let files = info_args
+            .get_many::<String>("files")
+            .unwrap_or_default()
+            .map(|v| v.as_str())
+            .collect::<Vec<_>>();
+        let errors = info::print_info(
+            &files,
+            info_args.get_one::<String>("format").unwrap(),
+            info_args.get_one::<u64>("scan_points").copied(),
+        );
+        return match errors {
+            0 => ExitCode::SUCCESS,
+            _ => ExitCode::from(2),
+        };
+    }
+
+    match args.subcommand() {
+        Some(("stats", stats_args)) => run(stats_args, Report::Stats(Stats::default())),
+        Some(("validate", validate_args)) => {
+            run(validate_args, Report::Validate(ValidationReport::default()))
+        }
+        Some(("coverage", coverage_args)) => {
+            let area = coverage_args.get_one::<Geometry>("geometry_file").unwrap();
+            let footprint = coverage_args.get_one::<String>("footprint").unwrap();
+            match Coverage::new(area, footprint.parse().unwrap()) {
+                Ok(coverage) => run(coverage_args, Report::Coverage(coverage)),
+                Err(error) => {
+                    eprintln!("error: {error}");
+                    ExitCode::from(2)
+                }
+            }
+        }
+        Some(("diff", diff_args)) => {
+            let inputs = diff_args
+                .get_many::<String>("input")
+                .unwrap_or_default()
+                .map(PathBuf::from)
+                .collect::<Vec<_>>();
+            if inputs[0] == inputs[1] {
+                eprintln!("error: the old and the new delivery are the same folder");
+                return ExitCode::from(2);
+            }
+            run(
+                diff_args,
+                Report::Diff {
+                    by: diff_args.get_one::<String>("by").unwrap().parse().unwrap(),
+                    limit: limit(diff_args),
+                    old: Vec::new(),
+                    new: Vec::new(),
+                },
+            )
+        }
+        _ => {
+            let report = match args.get_many::<String>("group_by") {
+                Some(attributes) => Report::GroupBy(GroupBy::new(attributes.cloned().collect())),
+                None if args.get_flag("stats") => Report::Stats(Stats::default()),
+                None if args.get_flag("count") => Report::Count {
+                    per_input: args.get_flag("per_input"),
+                    counts: args
+                        .get_many::<String>("input")
+                        .unwrap_or_default()
+                        .map(|input| (PathBuf::from(input), 0))
+                        .collect(),
+                },
+                None if args.get_flag("quiet") => Report::Quiet,
+                None if args.get_flag("duplicates") => Report::Duplicates(Duplicates::new(
+                    args.get_one::<String>("overlap")
+                        .map(|overlap| duplicates::parse_overlap(overlap).unwrap()),
+                    args.get_one::<String>("footprint")
+                        .unwrap()
+                        .parse()
+                        .unwrap(),
+                )),
+                None => Report::List,
+            };
+            run(&args, report)
+        }
+    }
+}
+
+/// Output of a run over the inputs.
+enum Report {
+    /// Lists the paths of the selected files
+    List,
+    Stats(Stats),
+    GroupBy(GroupBy),
+    /// Counts the selected files, in total or by input
+    Count {
+        per_input: bool,
+        counts: Vec<(PathBuf, u64)>,
+    },
+    /// Prints nothing, only the exit status tells if files are selected
+    Quiet,
+    /// Prints the issues of the headers
+    Validate(ValidationReport),
+    /// Pairs the duplicated files
+    Duplicates(Duplicates),
+    /// Unions the footprints over a reference area
+    Coverage(Coverage),
+    /// Compares the files of the first input with the files of the second one
+    Diff {
+        by: PairBy,
+        /// Number of differences printed, the files being all compared
+        limit: Option<usize>,
+        old: Vec<MatchedFile>,
+        new: Vec<MatchedFile>,
+    },
+}
+
+impl Report {
+    /// Footprint of the files compared by the report, if it compares them.
+    fn footprint(&self) -> Option<FootprintSource> {
+        match self {
+            Report::Duplicates(duplicates) => Some(duplicates.footprint),
+            Report::Coverage(coverage) => Some(coverage.footprint),
+            _ => None,
+        }
+    }
+
+    fn add(&mut self, file: &MatchedFile) {
+        match self {
+            Report::List => println!("{}", file.path.display()),
+            Report::Stats(stats) => stats.add(&file.header, file.size),
+            Report::GroupBy(group_by) => group_by.add(&file.header),
+            Report::Count { counts, .. } => {
+                if let Some((_, count)) = counts.get_mut(file.input) {
+                    *count += 1;
+                }
+            }
+            Report::Quiet => {}
+            Report::Validate(validation) => validation.add(
+                &file.path,
+                file.header.issues.as_deref().unwrap_or_default(),
+            ),
+            Report::Duplicates(duplicates) => {
+                duplicates.add(file.path.clone(), file.header.clone())
+            }
+            Report::Coverage(coverage) => coverage.add(&file.header),
+            Report::Diff { old, new, .. } => match file.input == 0 {
+                true => old.push(file.clone()),
+                false => new.push(file.clone()),
+            },
+        }
+    }
+
+    /// Prints the report, and returns the number of failures making the command exit with 1:
+    /// invalid files, differences, duplicate pairs or gaps.
+    fn print(self, format: &str) -> usize {
+        // Validation fails as soon as an invalid file is found, the duplicates check as soon as
+        // a pair is found and the coverage as soon as a gap is found
+        let invalid = match &self {
+            Report::Coverage(coverage) => coverage.compute().1 .0.len(),
+            Report::Validate(validation) => validation.invalid_count(),
+            Report::Duplicates(duplicates) => duplicates.pairs().len(),
+            _ => 0,
+        };
+        match (self, format) {
+            (Report::List, _) | (Report::Quiet, _) => {}
+            (Report::Count { per_input, counts }, _) => match per_input {
+                true => {
+                    for (root, count) in counts {
+                        println!("{}: {count}", root.display())
+                    }
+                }
+                false => println!("{}", counts.iter().map(|(_, count)| count).sum::<u64>()),
+            },
+            (Report::Stats(stats), "json") => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&stats.to_json()).unwrap()
+                )
+            }
+            (Report::Stats(stats), "csv") => println!("{}", stats.to_csv()),
+            (Report::Stats(stats), _) => println!("{}", stats.to_text()),
+            (Report::GroupBy(group_by), "json") => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&group_by.to_json()).unwrap()
+                )
+            }
+            (Report::GroupBy(group_by), "csv") => println!("{}", group_by.to_csv()),
+            (Report::GroupBy(group_by), _) => println!("{}", group_by.to_text()),
+            (Report::Validate(validation), "json") => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&validation.to_json()).unwrap()
+                )
+            }
+            (Report::Validate(validation), "csv") => println!("{}", validation.to_csv()),
+            (Report::Validate(validation), _) => println!("{}", validation.to_text()),
+            (Report::Duplicates(duplicates), "json") => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&duplicates.to_json()).unwrap()
+                )
+            }
+            (Report::Duplicates(duplicates), "csv") => println!("{}", duplicates.to_csv()),
+            (Report::Duplicates(duplicates), _) => println!("{}", duplicates.to_text()),
+            (Report::Coverage(coverage), "json") => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&coverage.to_json()).unwrap()
+                )
+            }
+            (Report::Coverage(coverage), "geojson") => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&coverage.to_geojson()).unwrap()
+                )
+            }
+            (Report::Coverage(coverage), _) => println!("{}", coverage.to_text()),
+            (
+                Report::Diff {
+                    by,
+                    limit,
+                    old,
+                    new,
+                },
+                format,
+            ) => {
+                // The diff fails as soon as a file differs, even beyond the limit
+                let mut diff = HeaderDiff::new(&old, &new, by);
+                let differs = !diff.is_empty();
+                diff.truncate(limit.unwrap_or(usize::MAX));
+                match format {
+                    "json" => {
+                        println!("{}", serde_json::to_string_pretty(&diff.to_json()).unwrap())
+                    }
+                    _ => println!("{}", diff.to_text()),
                 }
+                return differs as usize;
             }
         }
-        filter.geom_filter = Some(Filter::FSpatial(SpatialFilter::new(geometry, predicate)))
+        invalid
     }
+}
+
+/// Details the selection of a file, for --explain: every filter with the header value it
+/// compares and its verdict.
+fn explanation(
+    filter: &LasHeaderFilter,
+    file: &MatchedFile,
+    selection: Result<bool, &MissingValueError>,
+) -> String {
+    let mut lines = vec![match selection {
+        Ok(true) => format!("{}: selected", file.path.display()),
+        Ok(false) => format!("{}: not selected", file.path.display()),
+        Err(error) => format!("{}: error ({error})", file.path.display()),
+    }];
+    for (filter, verdict) in filter.evaluate(&file.header) {
+        lines.push(format!(
+            "  {filter} | {} | {verdict}",
+            filter.header_value(&file.header)
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Walks the inputs and lists the matching files, or reports about them.
+///
+/// The exit status follows grep: success when files are selected, 1 when none is, 2 on errors
+/// (unless files are selected in quiet mode).
+fn run(args: &ArgMatches, mut report: Report) -> ExitCode {
+    let filter = match build_filter(args) {
+        Ok(filter) => filter,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::from(2);
+        }
+    };
+    debug!("Query: {filter}");
 
     // Extraction of path
     let paths = args
@@ -406,30 +1500,109 @@ fn main() {
         .map(|v| v.as_str())
         .collect::<Vec<_>>();
 
-    let extensions = args
-        .get_many::<String>("extensions")
-        .unwrap_or_default()
-        .map(|v| v.as_str())
-        .collect::<Vec<_>>();
+    // --explain is not an option of the stats subcommand
+    let explain = matches!(args.try_get_one::<bool>("explain"), Ok(Some(true)));
+    let inverse = args.get_flag("invert");
 
-    match args.get_flag("debug") {
-        true => {
-            println!("Paths: {:?}", &paths);
-            println!("Extensions: {:?}", &extensions);
-            println!("Filters: {:?}", &filter);
-        }
-        false => {
-            // Main code
-            for path in paths {
-                folder_walk(
-                    path,
-                    args.get_flag("recursive"),
-                    args.get_flag("follow_links"),
-                    args.get_flag("invert"),
-                    &extensions,
-                    &filter,
-                )
+    // Explained files are all walked, to be filtered here with the reason of their rejection
+    let explained = LasHeaderFilter::new();
+    let walk_filter = match explain {
+        true => &explained,
+        false => &filter,
+    };
+    let options = WalkOptions {
+        recursive: args.get_flag("recursive"),
+        follow_links: args.get_flag("follow_links"),
+        inverse: inverse && !explain,
+        extensions: args
+            .get_many::<String>("extensions")
+            .unwrap_or_default()
+            .cloned()
+            .collect::<Vec<_>>(),
+        scan_points: args.get_one::<u64>("scan_points").copied(),
+        validate: filter.needs_validation() || matches!(report, Report::Validate(_)),
+        sidecars: filter.needs_sidecars() || report.footprint() == Some(FootprintSource::Sidecar),
+        names: tile_names(args),
+    };
+    if filter.needs_points() && options.scan_points.is_none() {
+        eprintln!("error: the filters on points need --scan-points");
+        return ExitCode::from(2);
+    }
+    if filter.needs_names() && options.names.is_none() {
+        eprintln!("error: the filters on name fields need --name-pattern");
+        return ExitCode::from(2);
+    }
+
+    let sort_key = args.get_one::<String>("sort_by");
+    // A diff compares all the files, its limit applies to the differences
+    let limit = match report {
+        Report::Diff { .. } => None,
+        _ => limit(args),
+    };
+    if args.get_flag("debug") {
+        println!("Paths: {:?}", &paths);
+        println!("Extensions: {:?}", &options.extensions);
+        println!("Filters: {filter}");
+        return ExitCode::SUCCESS;
+    }
+
+    // Files are buffered only when they have to be sorted
+    let mut matches = Vec::new();
+    let mut count = 0;
+    let mut explain_errors = 0;
+    let mut on_file = |file: MatchedFile, selected: bool| {
+        if explain {
+            let selection = filter
+                .try_filter(&file.header)
+                .map(|result| inverse ^ result);
+            println!(
+                "{}",
+                explanation(&filter, &file, selection.as_ref().copied())
+            );
+            match selection {
+                Ok(true) => count += 1,
+                Ok(false) => {}
+                Err(_) => explain_errors += 1,
+            }
+        } else if selected {
+            if sort_key.is_some() {
+                matches.push(file);
+            } else if limit.is_none_or(|limit| count < limit) {
+                count += 1;
+                report.add(&file);
             }
         }
+    };
+
+    // Main code
+    let mut errors = 0;
+    for (input, path) in paths.into_iter().enumerate() {
+        errors += folder_walk(input, path, &options, walk_filter, &mut on_file);
+    }
+    errors += explain_errors;
+
+    if let Some(sort_key) = sort_key {
+        sort::sort_matches(
+            &mut matches,
+            sort_key,
+            args.get_flag("reverse"),
+            filter.spatial_filter(),
+        );
+        matches.truncate(limit.unwrap_or(usize::MAX));
+        count = matches.len();
+        for file in &matches {
+            report.add(file);
+        }
+    }
+
+    let quiet = matches!(report, Report::Quiet);
+    let invalid = report.print(args.get_one::<String>("format").unwrap());
+
+    match (count, errors) {
+        (1.., _) if quiet => ExitCode::SUCCESS,
+        (_, 1..) => ExitCode::from(2),
+        (0, _) => ExitCode::from(1),
+        _ if invalid > 0 => ExitCode::from(1),
+        _ => ExitCode::SUCCESS,
     }
 }
@@ -1,81 +1,278 @@
 use crate::filter::Compare;
 use crate::header::MyHeader;
-use regex::Regex;
+use std::fmt::{Display, Formatter};
+
+/// Condition applied to a numeric header attribute.
+#[derive(PartialEq, Debug)]
+pub enum NumericCondition {
+    /// Comparison with a single value (eg. ">1000", "!=2")
+    Compare { operator: String, value: f64 },
+    /// Inclusive range, each bound being optional (eg. "10..20", "-5..", "..1M")
+    Range { min: Option<f64>, max: Option<f64> },
+    /// Set membership (eg. "in {1,2,3}")
+    In(Vec<f64>),
+}
 
 #[derive(PartialEq, Debug)]
 pub struct NumericFilter {
-    operator: String,
+    condition: NumericCondition,
     header_attribute: String,
-    value: f64,
 }
 
-impl NumericFilter {
-    pub fn new(input_string: &String, attribute_name: &String) -> NumericFilter {
-        let re: Regex =
-            Regex::new(r"(?P<operator>=|<=|>=|<|>|!=)(?P<value>[0-9]*(.[0-9]*))?").unwrap();
+/// Parses a number, with an optional sign, exponent and `k`, `M` or `G` suffix.
+pub fn parse_number(input: &str) -> Result<f64, String> {
+    let input = input.trim();
+    let (number, multiplier) = match input.chars().last() {
+        Some('k') => (&input[..input.len() - 1], 1e3),
+        Some('M') => (&input[..input.len() - 1], 1e6),
+        Some('G') => (&input[..input.len() - 1], 1e9),
+        _ => (input, 1.),
+    };
+
+    match number.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value * multiplier),
+        _ => Err(format!("invalid number \"{input}\"")),
+    }
+}
+
+/// Parses a numeric condition: "<op><number>", "<number>", "<min>..<max>" or "in {<numbers>}".
+pub fn parse_condition(input: &str) -> Result<NumericCondition, String> {
+    let input = input.trim();
 
-        let groups = match re.captures(input_string) {
-            Some(groups) => groups,
-            None => panic!("Syntax error for --points-number parameter"),
+    if let Some(set) = input.strip_prefix("in") {
+        let set = set.trim();
+        let values = set
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .or_else(|| set.strip_prefix('[').and_then(|s| s.strip_suffix(']')))
+            .ok_or(format!("invalid set \"{set}\", expected \"{{1,2,3}}\""))?;
+        return values
+            .split(',')
+            .map(parse_number)
+            .collect::<Result<Vec<_>, _>>()
+            .map(NumericCondition::In);
+    }
+
+    if let Some((min, max)) = input.split_once("..") {
+        let parse_bound = |bound: &str| match bound.trim() {
+            "" => Ok(None),
+            bound => parse_number(bound).map(Some),
         };
+        return match (parse_bound(min)?, parse_bound(max)?) {
+            (None, None) => Err(String::from("a range needs at least one bound")),
+            (Some(min), Some(max)) if min > max => {
+                Err(format!("empty range, {min} is greater than {max}"))
+            }
+            (min, max) => Ok(NumericCondition::Range { min, max }),
+        };
+    }
 
-        NumericFilter {
-            operator: groups.name("operator").unwrap().as_str().to_string(),
+    let operator = ["<=", ">=", "!=", "=", "<", ">"]
+        .into_iter()
+        .find(|operator| input.starts_with(operator))
+        .unwrap_or("=");
+    let value = parse_number(input.strip_prefix(operator).unwrap_or(input))?;
+
+    Ok(NumericCondition::Compare {
+        operator: operator.to_string(),
+        value,
+    })
+}
+
+/// Checks the syntax of a numeric condition (used to validate command line arguments).
+pub fn check_syntax(input: &str) -> Result<String, String> {
+    parse_condition(input).map(|_| input.to_string())
+}
+
+impl NumericFilter {
+    pub fn new(input_string: &str, attribute_name: &str) -> Result<NumericFilter, String> {
+        Ok(NumericFilter {
+            condition: parse_condition(input_string)?,
             header_attribute: attribute_name.to_string(),
-            value: groups
-                .name("value")
-                .unwrap()
-                .as_str()
-                .parse::<f64>()
-                .unwrap(),
+        })
+    }
+}
+
+impl Display for NumericCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumericCondition::Compare { operator, value } => write!(f, "{operator}{value}"),
+            NumericCondition::Range { min, max } => {
+                let bound = |bound: &Option<f64>| bound.map_or(String::new(), |b| b.to_string());
+                write!(f, "{}..{}", bound(min), bound(max))
+            }
+            NumericCondition::In(values) => write!(
+                f,
+                "in {{{}}}",
+                values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
         }
     }
 }
 
-impl Compare for NumericFilter {
-    fn compare(&self, value: &MyHeader) -> bool {
-        let operator = self.operator.as_str();
-
-        match self.header_attribute.as_str() {
-            "number_of_points" => match operator {
-                "=" => self.value == value.number_of_points as f64,
-                "!=" => self.value != value.number_of_points as f64,
-                ">" => self.value < value.number_of_points as f64,
-                "<" => self.value > value.number_of_points as f64,
-                "<=" => self.value >= value.number_of_points as f64,
-                ">=" => self.value <= value.number_of_points as f64,
+impl Display for NumericFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.header_attribute, self.condition)
+    }
+}
+
+impl NumericCondition {
+    pub fn matches(&self, header_value: f64) -> bool {
+        match self {
+            NumericCondition::Compare { operator, value } => match operator.as_str() {
+                "=" => *value == header_value,
+                "!=" => *value != header_value,
+                ">" => *value < header_value,
+                "<" => *value > header_value,
+                "<=" => *value >= header_value,
+                ">=" => *value <= header_value,
                 _ => panic!("Operator {} not found", operator),
             },
-            "file_source_id" => match operator {
-                "=" => self.value == value.file_source_id as f64,
-                "!=" => self.value != value.file_source_id as f64,
-                ">" => self.value < value.file_source_id as f64,
-                "<" => self.value > value.file_source_id as f64,
-                "<=" => self.value >= value.file_source_id as f64,
-                ">=" => self.value <= value.file_source_id as f64,
+            NumericCondition::Range { min, max } => {
+                min.is_none_or(|min| min <= header_value)
+                    && max.is_none_or(|max| header_value <= max)
+            }
+            NumericCondition::In(values) => values.contains(&header_value),
+        }
+    }
+
+    /// Returns true if a value of the inclusive range `min..max` may match the condition.
+    pub fn overlaps(&self, min: f64, max: f64) -> bool {
+        match self {
+            NumericCondition::Compare { operator, value } => match operator.as_str() {
+                "=" => min <= *value && *value <= max,
+                "!=" => !(min == *value && max == *value),
+                ">" => *value < max,
+                "<" => *value > min,
+                "<=" => *value >= min,
+                ">=" => *value <= max,
                 _ => panic!("Operator {} not found", operator),
             },
-            _ => false,
+            NumericCondition::Range {
+                min: range_min,
+                max: range_max,
+            } => {
+                range_min.is_none_or(|range_min| range_min <= max)
+                    && range_max.is_none_or(|range_max| min <= range_max)
+            }
+            NumericCondition::In(values) => values.iter().any(|v| min <= *v && *v <= max),
+        }
+    }
+}
+
+impl Compare for NumericFilter {
+    fn attribute(&self) -> &str {
+        &self.header_attribute
+    }
+
+    fn header_value(&self, value: &MyHeader) -> String {
+        if let Some(accessor) = MyHeader::range_field(&self.header_attribute) {
+            return accessor(value).map_or(String::from("-"), |(min, max)| format!("{min}..{max}"));
+        }
+        value
+            .numeric_value(&self.header_attribute)
+            .map_or(String::from("-"), |header_value| header_value.to_string())
+    }
+
+    fn compare(&self, value: &MyHeader) -> bool {
+        if let Some(accessor) = MyHeader::range_field(&self.header_attribute) {
+            return accessor(value).is_some_and(|(min, max)| self.condition.overlaps(min, max));
         }
+        value
+            .numeric_value(&self.header_attribute)
+            .is_some_and(|header_value| self.condition.matches(header_value))
     }
 }
 
 #[test]
 fn test_xx() {
     assert_eq!(
-        NumericFilter::new(&"<10.0".to_string(), &"".to_string()),
+        NumericFilter::new("<10.0", "").unwrap(),
         NumericFilter {
-            operator: "<".to_string(),
+            condition: NumericCondition::Compare {
+                operator: "<".to_string(),
+                value: 10.0
+            },
             header_attribute: "".to_string(),
-            value: 10.0
         }
     );
     assert_eq!(
-        NumericFilter::new(&"<=15000".to_string(), &"".to_string()),
+        NumericFilter::new("<=15000", "").unwrap(),
         NumericFilter {
-            operator: "<=".to_string(),
+            condition: NumericCondition::Compare {
+                operator: "<=".to_string(),
+                value: 15000.0
+            },
             header_attribute: "".to_string(),
-            value: 15000.0
         }
     )
 }
+
+#[test]
+fn test_numeric_grammar() {
+    assert_eq!(parse_number("-12.5"), Ok(-12.5));
+    assert_eq!(parse_number("1e6"), Ok(1e6));
+    assert_eq!(parse_number("10M"), Ok(1e7));
+    assert_eq!(parse_number("2.5k"), Ok(2500.));
+    assert!(parse_number("1x").is_err());
+    assert_eq!(
+        parse_condition("<=1e6"),
+        Ok(NumericCondition::Compare {
+            operator: "<=".to_string(),
+            value: 1e6
+        })
+    );
+    assert_eq!(
+        parse_condition("-5..1G"),
+        Ok(NumericCondition::Range {
+            min: Some(-5.),
+            max: Some(1e9)
+        })
+    );
+    assert_eq!(
+        parse_condition("10.."),
+        Ok(NumericCondition::Range {
+            min: Some(10.),
+            max: None
+        })
+    );
+    assert_eq!(
+        parse_condition("in {1, 2,3}"),
+        Ok(NumericCondition::In(vec![1., 2., 3.]))
+    );
+    assert!(parse_condition("<=1.2.3").is_err());
+    assert!(parse_condition("5..1").is_err());
+}
+
+#[test]
+fn test_numeric_fields() {
+    let header = MyHeader::new(&las::Header::default());
+    assert!(NumericFilter::new("=0", "number_of_points")
+        .unwrap()
+        .compare(&header));
+    assert!(NumericFilter::new(">=20", "point_data_record_length")
+        .unwrap()
+        .compare(&header));
+    assert!(NumericFilter::new("=0.001", "scale_x")
+        .unwrap()
+        .compare(&header));
+    assert!(!NumericFilter::new("=0", "unknown_field")
+        .unwrap()
+        .compare(&header));
+}
+
+#[test]
+fn test_range_overlap() {
+    let overlaps =
+        |input: &str, min: f64, max: f64| parse_condition(input).unwrap().overlaps(min, max);
+    assert!(overlaps(">5", 0.0, 10.0));
+    assert!(!overlaps(">10", 0.0, 10.0));
+    assert!(overlaps("=3", 0.0, 10.0));
+    assert!(overlaps("8..20", 0.0, 10.0));
+    assert!(!overlaps("..-1", 0.0, 10.0));
+    assert!(overlaps("in {12, 7}", 0.0, 10.0));
+}
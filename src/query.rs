@@ -0,0 +1,172 @@
+use crate::bool_filter::BoolFilter;
+use crate::date_filter::DateFilter;
+use crate::filter::{Filter, LasHeaderFilter};
+use crate::footprint::FootprintSource;
+use crate::header::MyHeader;
+use crate::list_filter::{parse_class_list, parse_point_format_list, ListFilter};
+use crate::numeric_filter::NumericFilter;
+use crate::spatial_filter::{SpatialFilter, SpatialPredicate};
+use crate::string_filter::StringFilter;
+use crate::tile_name::NAME_PREFIX;
+
+use geo::Geometry;
+use wkt::TryFromWkt;
+
+/// Date format of the dates written in queries.
+pub const QUERY_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Header attributes compared to a string.
+const STRING_ATTRIBUTES: [&str; 5] = [
+    "gps_time_type",
+    "version",
+    "guid",
+    "generating_software",
+    "system_identifier",
+];
+
+/// Quotes a value of a query, escaping the quotes and backslashes it contains.
+pub fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Reverts [`quote`], returns `None` if `input` is not a single quoted value.
+fn unquote(input: &str) -> Option<String> {
+    let mut chars = input.strip_prefix('"')?.chars();
+    let mut value = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            '"' => return chars.as_str().is_empty().then_some(value),
+            _ => value.push(c),
+        }
+    }
+    None
+}
+
+/// Splits a query on the " and " separating its clauses, except inside quoted values.
+fn split_clauses(query: &str) -> Vec<&str> {
+    let mut clauses = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (index, c) in query.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ' ' if !quoted && index >= start && query[index..].starts_with(" and ") => {
+                clauses.push(&query[start..index]);
+                start = index + " and ".len();
+            }
+            _ => {}
+        }
+    }
+    clauses.push(&query[start..]);
+    clauses
+}
+
+/// Parses a clause of a query, an attribute name followed by its condition.
+fn parse_clause(clause: &str) -> Result<Filter, String> {
+    let (attribute, condition) = clause
+        .trim()
+        .split_once(' ')
+        .ok_or(format!("missing condition in \"{clause}\""))?;
+    let condition = condition.trim();
+    // Former name of the version, as the --las-version option
+    let attribute = match attribute {
+        "las_version" => "version",
+        attribute => attribute,
+    };
+    let quoted = || unquote(condition).ok_or(format!("expected a quoted value for {attribute}"));
+
+    let filter = match attribute {
+        "date" => Filter::FDate(DateFilter::new(condition, QUERY_DATE_FORMAT, attribute)?),
+        "point_format" => Filter::FList(ListFilter::new(
+            parse_point_format_list(condition)?,
+            attribute,
+        )),
+        "has_class" => Filter::FList(ListFilter::new(parse_class_list(condition)?, attribute)),
+        "bbox" | "hull" | "sidecar" => {
+            let (predicate, geometry) = condition
+                .split_once(' ')
+                .ok_or(format!("missing geometry in \"{clause}\""))?;
+            let geometry = unquote(geometry.trim())
+                .ok_or(format!("expected a quoted WKT geometry for {attribute}"))?;
+            let geometry = Geometry::try_from_wkt_str(&geometry)
+                .map_err(|error| format!("invalid WKT geometry: {error}"))?;
+            Filter::FSpatial(SpatialFilter::new(
+                geometry,
+                predicate.parse::<SpatialPredicate>()?,
+                attribute.parse::<FootprintSource>()?,
+            )?)
+        }
+        // Name captures are matched as regexes when quoted, as numbers otherwise
+        _ if attribute.starts_with(NAME_PREFIX) => match unquote(condition) {
+            Some(pattern) => Filter::FString(StringFilter::new(&pattern, attribute)?),
+            None => Filter::FNumeric(NumericFilter::new(condition, attribute)?),
+        },
+        _ if STRING_ATTRIBUTES.contains(&attribute) => {
+            Filter::FString(StringFilter::new(&quoted()?, attribute)?)
+        }
+        _ if MyHeader::numeric_field(attribute).is_some()
+            || MyHeader::range_field(attribute).is_some() =>
+        {
+            Filter::FNumeric(NumericFilter::new(condition, attribute)?)
+        }
+        _ if MyHeader::bool_field(attribute).is_some() => match condition.parse::<bool>() {
+            Ok(value) => Filter::FBool(BoolFilter::new(value, attribute)),
+            Err(_) => return Err(format!("expected true or false for {attribute}")),
+        },
+        _ => return Err(format!("unknown attribute \"{attribute}\"")),
+    };
+    Ok(filter)
+}
+
+/// Parses a query, as written by the `Display` implementation of [`LasHeaderFilter`].
+///
+/// A query is a list of clauses separated by "and", eg.
+/// `number_of_points >1000 and generating_software "TerraScan.*" and date 2023`.
+pub fn parse_query(query: &str) -> Result<LasHeaderFilter, String> {
+    let mut filter = LasHeaderFilter::new();
+    if query.trim().is_empty() {
+        return Ok(filter);
+    }
+
+    for clause in split_clauses(query) {
+        let clause_filter =
+            parse_clause(clause).map_err(|error| format!("Syntax error in query: {error}"))?;
+        filter.add(clause_filter);
+    }
+    Ok(filter)
+}
+
+#[test]
+fn test_query_round_trip() {
+    let query = "number_of_points >1000 and gps_time_type \"standard\" \
+        and generating_software \"Terra \\\"Scan\\\" and co\" and date 2022..2023-06-30 \
+        and point_format in {6,7} and bbox centroid_distance(500) \"POINT(10 10)\" \
+        and min_x -5..3 and has_color true";
+    let filter: LasHeaderFilter = query.parse().unwrap();
+    assert_eq!(
+        filter
+            .to_string()
+            .parse::<LasHeaderFilter>()
+            .unwrap()
+            .to_string(),
+        filter.to_string()
+    );
+    assert_eq!(filter.attribute_filters.len(), 2);
+    assert!(filter
+        .to_string()
+        .contains("generating_software \"Terra \\\"Scan\\\" and co\""));
+
+    // The version is named as in the header fields and --group-by, las_version being an alias
+    let version = parse_query("version \">=1.4\"").unwrap();
+    assert_eq!(version.to_string(), "version \">=1.4\"");
+    assert_eq!(
+        parse_query("las_version \">=1.4\"").unwrap().to_string(),
+        version.to_string()
+    );
+
+    assert!(parse_query("colour true").is_err());
+    assert!(parse_query("guid 12").is_err());
+    assert!(parse_query("").unwrap().to_string().is_empty());
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use geo::{ConvexHull, Coord, MultiPoint, Polygon};
+use las::{Bounds, Point, Read, Reader, Vector};
+use serde_json::{json, Value};
+
+/// Statistics of the points of a file, read with --scan-points.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PointStats {
+    /// Number of points read, less than the number of points of the file when sampling
+    pub points_read: u64,
+    /// Actual bounds of the points read, if any
+    pub bounds: Option<Bounds>,
+    /// Number of points read by classification
+    pub classes: BTreeMap<u8, u64>,
+    pub intensity: Option<(u16, u16)>,
+    pub gps_time: Option<(f64, f64)>,
+    /// Points read, reduced to the vertices of their convex hull when there are too many
+    hull_points: Vec<Coord>,
+}
+
+/// Number of points buffered before their reduction to their convex hull.
+const HULL_BUFFER: usize = 1 << 16;
+
+fn convex_hull(points: &[Coord]) -> Polygon {
+    MultiPoint::from(points.to_vec()).convex_hull()
+}
+
+impl PointStats {
+    /// Reads one point every `every` points of the reader.
+    ///
+    /// Sampling seeks in las files, laz files being decompressed entirely.
+    pub fn scan(reader: &mut Reader, every: u64) -> Result<PointStats, String> {
+        let mut stats = PointStats::default();
+        let header = reader.header();
+        let (compressed, number_of_points) = (
+            header.point_format().is_compressed,
+            header.number_of_points(),
+        );
+
+        if compressed || every == 1 {
+            for point in reader.points().step_by(every as usize) {
+                stats.add(&point.map_err(|error| error.to_string())?);
+            }
+        } else {
+            for index in (0..number_of_points).step_by(every as usize) {
+                reader.seek(index).map_err(|error| error.to_string())?;
+                match reader.read() {
+                    Some(point) => stats.add(&point.map_err(|error| error.to_string())?),
+                    None => break,
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    pub fn add(&mut self, point: &Point) {
+        self.points_read += 1;
+
+        let bounds = self.bounds.get_or_insert(Bounds {
+            min: Vector {
+                x: point.x,
+                y: point.y,
+                z: point.z,
+            },
+            max: Vector {
+                x: point.x,
+                y: point.y,
+                z: point.z,
+            },
+        });
+        bounds.min.x = bounds.min.x.min(point.x);
+        bounds.min.y = bounds.min.y.min(point.y);
+        bounds.min.z = bounds.min.z.min(point.z);
+        bounds.max.x = bounds.max.x.max(point.x);
+        bounds.max.y = bounds.max.y.max(point.y);
+        bounds.max.z = bounds.max.z.max(point.z);
+
+        *self.classes.entry(point.classification.into()).or_default() += 1;
+
+        let (min, max) = self.intensity.unwrap_or((point.intensity, point.intensity));
+        self.intensity = Some((min.min(point.intensity), max.max(point.intensity)));
+
+        if let Some(gps_time) = point.gps_time {
+            let (min, max) = self.gps_time.unwrap_or((gps_time, gps_time));
+            self.gps_time = Some((min.min(gps_time), max.max(gps_time)));
+        }
+
+        self.hull_points.push(Coord {
+            x: point.x,
+            y: point.y,
+        });
+        if self.hull_points.len() >= HULL_BUFFER {
+            self.hull_points = convex_hull(&self.hull_points).exterior().0.clone();
+        }
+    }
+
+    /// Convex hull of the points read, the footprint of the file with `--footprint hull`.
+    pub fn hull(&self) -> Option<Polygon> {
+        match self.hull_points.is_empty() {
+            true => None,
+            false => Some(convex_hull(&self.hull_points)),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "points_read": self.points_read,
+            "min": self.bounds.map(|b| [b.min.x, b.min.y, b.min.z]),
+            "max": self.bounds.map(|b| [b.max.x, b.max.y, b.max.z]),
+            "classes": self
+                .classes
+                .iter()
+                .map(|(class, count)| (class.to_string(), json!(count)))
+                .collect::<serde_json::Map<_, _>>(),
+            "intensity": self.intensity.map(|(min, max)| [min, max]),
+            "gps_time": self.gps_time.map(|(min, max)| [min, max]),
+        })
+    }
+}
+
+#[test]
+fn test_point_stats() {
+    let mut stats = PointStats::default();
+    for (x, class, gps_time) in [(1.0, 2, Some(10.0)), (-3.0, 9, None), (5.0, 2, Some(4.5))] {
+        stats.add(&Point {
+            x,
+            classification: las::point::Classification::new(class).unwrap(),
+            intensity: (x * 10.0).abs() as u16,
+            gps_time,
+            ..Default::default()
+        });
+    }
+
+    assert_eq!(stats.points_read, 3);
+    assert_eq!(stats.bounds.map(|b| (b.min.x, b.max.x)), Some((-3.0, 5.0)));
+    assert_eq!(stats.classes, BTreeMap::from([(2, 2), (9, 1)]));
+    assert_eq!(stats.intensity, Some((10, 50)));
+    assert_eq!(stats.gps_time, Some((4.5, 10.0)));
+    assert!(stats.hull().is_some());
+    assert!(PointStats::default().hull().is_none());
+}
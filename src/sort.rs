@@ -0,0 +1,102 @@
+use std::cmp::Ordering;
+
+use geo::EuclideanDistance;
+
+use crate::spatial_filter::SpatialFilter;
+use crate::walk::MatchedFile;
+
+/// Attributes the selected files can be sorted by.
+pub const SORT_KEYS: [&str; 5] = ["points", "date", "size", "name", "distance"];
+
+/// Sorts the selected files by `key`, the distance being computed from the footprints of the
+/// spatial filter to its geometry.
+///
+/// Files without date are sorted first, as if they were the oldest, and files without
+/// footprint last by distance.
+pub fn sort_matches(
+    matches: &mut Vec<MatchedFile>,
+    key: &str,
+    reverse: bool,
+    spatial_filter: Option<&SpatialFilter>,
+) {
+    if key == "distance" {
+        let distance = |file: &MatchedFile| {
+            spatial_filter.map_or(0., |filter| {
+                file.header
+                    .footprint(filter.footprint())
+                    .map_or(f64::INFINITY, |footprint| {
+                        footprint.euclidean_distance(filter.geometry())
+                    })
+            })
+        };
+        // The distances are computed once, not at each comparison
+        let mut distances = matches
+            .drain(..)
+            .map(|file| (distance(&file), file))
+            .collect::<Vec<_>>();
+        match reverse {
+            true => distances.sort_by(|(a, _), (b, _)| b.total_cmp(a)),
+            false => distances.sort_by(|(a, _), (b, _)| a.total_cmp(b)),
+        }
+        matches.extend(distances.into_iter().map(|(_, file)| file));
+        return;
+    }
+
+    let compare = |a: &MatchedFile, b: &MatchedFile| -> Ordering {
+        match key {
+            "points" => a.header.number_of_points.cmp(&b.header.number_of_points),
+            "date" => a.header.date.cmp(&b.header.date),
+            "size" => a.size.cmp(&b.size),
+            "name" => a.path.file_name().cmp(&b.path.file_name()),
+            _ => panic!("Sort key {} not found", key),
+        }
+    };
+
+    match reverse {
+        true => matches.sort_by(|a, b| compare(b, a)),
+        false => matches.sort_by(compare),
+    }
+}
+
+#[test]
+fn test_sort_matches() {
+    use crate::header::MyHeader;
+    use std::path::PathBuf;
+
+    let file = |name: &str, size: u64| MatchedFile {
+        input: 0,
+        root: PathBuf::from("."),
+        path: PathBuf::from(name),
+        size,
+        header: MyHeader::new(&las::Header::default()),
+    };
+    let mut matches = vec![file("b.las", 10), file("a.las", 30), file("c.las", 20)];
+
+    sort_matches(&mut matches, "name", false, None);
+    assert_eq!(matches[0].path, PathBuf::from("a.las"));
+    sort_matches(&mut matches, "size", true, None);
+    assert_eq!(
+        matches.iter().map(|file| file.size).collect::<Vec<_>>(),
+        vec![30, 20, 10]
+    );
+
+    // The distance is measured from the footprint of the spatial filter, the sidecar here
+    use crate::footprint::FootprintSource;
+    use crate::spatial_filter::SpatialPredicate;
+    let point = geo::Geometry::Point(geo::Point::new(100.0, 0.0));
+    let filter = SpatialFilter::new(
+        point,
+        SpatialPredicate::Distance { dist: 1000.0 },
+        FootprintSource::Sidecar,
+    )
+    .unwrap();
+    let sidecar = |x: f64| Some(geo::Geometry::Point(geo::Point::new(x, 0.0)));
+    matches[0].header.sidecar = sidecar(0.0);
+    matches[1].header.sidecar = None;
+    matches[2].header.sidecar = sidecar(90.0);
+    sort_matches(&mut matches, "distance", false, Some(&filter));
+    assert_eq!(
+        matches.iter().map(|file| file.size).collect::<Vec<_>>(),
+        vec![10, 30, 20]
+    );
+}
@@ -1,8 +1,13 @@
 use crate::filter::Compare;
+use crate::footprint::FootprintSource;
 use crate::header::MyHeader;
+use crate::query::quote;
 
-use geo::Centroid;
-use geo::{Contains, EuclideanDistance, Intersects, Within};
+use geo::{BoundingRect, Centroid};
+use geo::{Contains, EuclideanDistance, Intersects, Relate, Within};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use wkt::ToWkt;
 
 #[derive(Debug)]
 pub enum SpatialPredicate {
@@ -10,34 +15,250 @@ pub enum SpatialPredicate {
     Within,
     CentroidWithin,
     Contains,
-    CentroidDistance { dist: f64 },
+    /// Centroid of the footprint at most `dist` from the centroid of the geometry
+    CentroidDistance {
+        dist: f64,
+    },
+    /// Footprint at most `dist` from the closest point of the geometry
+    Distance {
+        dist: f64,
+    },
+    Disjoint,
+    Touches,
+    Overlaps,
+    Crosses,
+    /// No point of the geometry is outside the footprint
+    Covers,
+    /// No point of the footprint is outside the geometry
+    CoveredBy,
+    /// DE-9IM pattern of the footprint relation to the geometry (eg. "T*F**F***")
+    Relate {
+        pattern: String,
+    },
+}
+
+/// Checks a DE-9IM pattern: 9 characters among T, F, *, 0, 1 and 2.
+pub fn check_relate_pattern(pattern: &str) -> Result<String, String> {
+    match pattern.len() == 9 && pattern.chars().all(|c| "TF*012".contains(c)) {
+        true => Ok(pattern.to_string()),
+        false => Err(format!(
+            "invalid DE-9IM pattern \"{pattern}\", expected 9 characters among T, F, *, 0, 1 \
+            and 2"
+        )),
+    }
+}
+
+impl Display for SpatialPredicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpatialPredicate::Intersects => write!(f, "intersects"),
+            SpatialPredicate::Within => write!(f, "within"),
+            SpatialPredicate::CentroidWithin => write!(f, "centroid_within"),
+            SpatialPredicate::Contains => write!(f, "contains"),
+            SpatialPredicate::CentroidDistance { dist } => write!(f, "centroid_distance({dist})"),
+            SpatialPredicate::Distance { dist } => write!(f, "distance({dist})"),
+            SpatialPredicate::Disjoint => write!(f, "disjoint"),
+            SpatialPredicate::Touches => write!(f, "touches"),
+            SpatialPredicate::Overlaps => write!(f, "overlaps"),
+            SpatialPredicate::Crosses => write!(f, "crosses"),
+            SpatialPredicate::Covers => write!(f, "covers"),
+            SpatialPredicate::CoveredBy => write!(f, "covered_by"),
+            SpatialPredicate::Relate { pattern } => write!(f, "relate({pattern})"),
+        }
+    }
+}
+
+impl FromStr for SpatialPredicate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "intersects" => Ok(SpatialPredicate::Intersects),
+            "within" => Ok(SpatialPredicate::Within),
+            "centroid_within" => Ok(SpatialPredicate::CentroidWithin),
+            "contains" => Ok(SpatialPredicate::Contains),
+            "disjoint" => Ok(SpatialPredicate::Disjoint),
+            "touches" => Ok(SpatialPredicate::Touches),
+            "overlaps" => Ok(SpatialPredicate::Overlaps),
+            "crosses" => Ok(SpatialPredicate::Crosses),
+            "covers" => Ok(SpatialPredicate::Covers),
+            "covered_by" => Ok(SpatialPredicate::CoveredBy),
+            _ => {
+                let argument = |name: &str| {
+                    s.strip_prefix(name)?
+                        .strip_prefix('(')?
+                        .strip_suffix(')')
+                        .map(str::trim)
+                };
+                if let Some(pattern) = argument("relate") {
+                    return check_relate_pattern(pattern)
+                        .map(|pattern| SpatialPredicate::Relate { pattern });
+                }
+                let distance = |dist: &str| match dist.parse::<f64>() {
+                    Ok(dist) if dist >= 0.0 => Ok(dist),
+                    _ => Err(format!("invalid distance \"{dist}\"")),
+                };
+                if let Some(dist) = argument("centroid_distance") {
+                    return distance(dist).map(|dist| SpatialPredicate::CentroidDistance { dist });
+                }
+                match argument("distance") {
+                    Some(dist) => distance(dist).map(|dist| SpatialPredicate::Distance { dist }),
+                    None => Err(format!("unknown spatial predicate \"{s}\"")),
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct SpatialFilter {
     predicate: SpatialPredicate,
     geom: geo::Geometry,
+    footprint: FootprintSource,
 }
 
 impl SpatialFilter {
-    pub fn new(geom: geo::Geometry, predicate: SpatialPredicate) -> SpatialFilter {
-        SpatialFilter { predicate, geom }
+    /// Fails on an empty geometry, a negative distance or an invalid DE-9IM pattern.
+    pub fn new(
+        geom: geo::Geometry,
+        predicate: SpatialPredicate,
+        footprint: FootprintSource,
+    ) -> Result<SpatialFilter, String> {
+        if geom.bounding_rect().is_none() {
+            return Err(String::from("the geometry of the spatial filter is empty"));
+        }
+        match &predicate {
+            SpatialPredicate::Distance { dist } | SpatialPredicate::CentroidDistance { dist }
+                if dist.is_nan() || *dist < 0.0 =>
+            {
+                return Err(format!("invalid distance {dist}"))
+            }
+            SpatialPredicate::Relate { pattern } => {
+                check_relate_pattern(pattern)?;
+            }
+            _ => {}
+        }
+        Ok(SpatialFilter {
+            predicate,
+            geom,
+            footprint,
+        })
+    }
+
+    pub fn geometry(&self) -> &geo::Geometry {
+        &self.geom
+    }
+
+    /// Footprint of the files compared to the geometry.
+    pub fn footprint(&self) -> FootprintSource {
+        self.footprint
     }
 }
 
 impl Compare for SpatialFilter {
+    fn attribute(&self) -> &str {
+        self.footprint.name()
+    }
+
+    fn header_value(&self, value: &MyHeader) -> String {
+        let bounds = value.bounds;
+        match self.footprint {
+            FootprintSource::Bbox => format!(
+                "BOX({} {}, {} {})",
+                bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y
+            ),
+            footprint => value
+                .footprint(footprint)
+                .map_or(String::from("-"), |footprint| footprint.wkt_string()),
+        }
+    }
+
     fn compare(&self, value: &MyHeader) -> bool {
+        let Some(footprint) = value.footprint(self.footprint) else {
+            return false;
+        };
         match self.predicate {
-            SpatialPredicate::Intersects => value.bbox.intersects(&self.geom),
-            SpatialPredicate::Contains => value.bbox.contains(&self.geom),
-            SpatialPredicate::Within => value.bbox.is_within(&self.geom),
-            SpatialPredicate::CentroidWithin => value.centroid.is_within(&self.geom),
+            SpatialPredicate::Intersects => footprint.intersects(&self.geom),
+            SpatialPredicate::Contains => footprint.contains(&self.geom),
+            SpatialPredicate::Within => footprint.is_within(&self.geom),
+            SpatialPredicate::CentroidWithin => footprint
+                .centroid()
+                .is_some_and(|centroid| centroid.is_within(&self.geom)),
             SpatialPredicate::CentroidDistance { dist } => {
-                value
-                    .centroid
-                    .euclidean_distance(&self.geom.centroid().unwrap())
-                    <= dist
+                match (footprint.centroid(), self.geom.centroid()) {
+                    (Some(centroid), Some(target)) => centroid.euclidean_distance(&target) <= dist,
+                    _ => false,
+                }
             }
+            SpatialPredicate::Distance { dist } => footprint.euclidean_distance(&self.geom) <= dist,
+            SpatialPredicate::Disjoint => footprint.relate(&self.geom).is_disjoint(),
+            SpatialPredicate::Touches => footprint.relate(&self.geom).is_touches(),
+            SpatialPredicate::Overlaps => footprint.relate(&self.geom).is_overlaps(),
+            SpatialPredicate::Crosses => footprint.relate(&self.geom).is_crosses(),
+            SpatialPredicate::Covers => footprint.relate(&self.geom).is_covers(),
+            SpatialPredicate::CoveredBy => footprint.relate(&self.geom).is_coveredby(),
+            SpatialPredicate::Relate { ref pattern } => footprint
+                .relate(&self.geom)
+                .matches(pattern)
+                .unwrap_or(false),
         }
     }
 }
+
+impl Display for SpatialFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.attribute(),
+            self.predicate,
+            quote(&self.geom.wkt_string())
+        )
+    }
+}
+
+#[test]
+fn test_topological_predicates() {
+    use wkt::TryFromWkt;
+
+    let mut builder = las::Builder::default();
+    builder.generating_software = String::from("lg");
+    let mut header = MyHeader::new(&builder.into_header().unwrap());
+    header.bbox = geo::Rect::new((0.0, 0.0), (10.0, 10.0)).to_polygon();
+
+    let selects = |predicate: &str, wkt: &str| {
+        let filter = SpatialFilter::new(
+            geo::Geometry::try_from_wkt_str(wkt).unwrap(),
+            predicate.parse().unwrap(),
+            FootprintSource::Bbox,
+        )
+        .unwrap();
+        filter.compare(&header)
+    };
+    let inside = "POLYGON((2 2, 8 2, 8 8, 2 8, 2 2))";
+    let beside = "POLYGON((10 0, 20 0, 20 10, 10 10, 10 0))";
+    assert!(selects("touches", beside) && !selects("touches", inside));
+    assert!(selects("disjoint", "POINT(50 50)") && !selects("disjoint", beside));
+    assert!(selects(
+        "overlaps",
+        "POLYGON((5 5, 15 5, 15 15, 5 15, 5 5))"
+    ));
+    assert!(selects("crosses", "LINESTRING(-5 5, 15 5)"));
+    assert!(selects("covers", inside) && selects("covers", "POINT(0 5)"));
+    assert!(selects(
+        "covered_by",
+        "POLYGON((0 0, 10 0, 10 20, 0 20, 0 0))"
+    ));
+    assert!(selects("relate(T*****FF*)", inside));
+    assert!("relate(T*F)".parse::<SpatialPredicate>().is_err());
+
+    // A road passing 5 units from the tile, whose centroid is far away
+    let road = "LINESTRING(-100 15, 100 15)";
+    assert!(selects("distance(5)", road) && !selects("distance(4.9)", road));
+    assert!(!selects("centroid_distance(5)", road));
+    assert!("distance(-1)".parse::<SpatialPredicate>().is_err());
+    let empty = geo::Geometry::GeometryCollection(geo::GeometryCollection::new_from(vec![]));
+    assert!(
+        SpatialFilter::new(empty, SpatialPredicate::Intersects, FootprintSource::Bbox).is_err()
+    );
+}
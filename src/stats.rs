@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use las::Bounds;
+use serde_json::{json, Value};
+
+use crate::header::MyHeader;
+
+/// Aggregation of the headers of the selected files.
+#[derive(Default, Debug)]
+pub struct Stats {
+    pub file_count: u64,
+    pub total_points: u64,
+    pub total_bytes: u64,
+    pub bounds: Option<Bounds>,
+    pub first_date: Option<NaiveDate>,
+    pub last_date: Option<NaiveDate>,
+    pub point_formats: BTreeMap<String, u64>,
+    pub versions: BTreeMap<String, u64>,
+    pub generating_softwares: BTreeMap<String, u64>,
+}
+
+impl Stats {
+    pub fn add(&mut self, header: &MyHeader, file_size: u64) {
+        self.file_count += 1;
+        self.total_points += header.number_of_points;
+        self.total_bytes += file_size;
+
+        self.bounds = Some(match self.bounds {
+            None => header.bounds,
+            Some(mut bounds) => {
+                bounds.min.x = bounds.min.x.min(header.bounds.min.x);
+                bounds.min.y = bounds.min.y.min(header.bounds.min.y);
+                bounds.min.z = bounds.min.z.min(header.bounds.min.z);
+                bounds.max.x = bounds.max.x.max(header.bounds.max.x);
+                bounds.max.y = bounds.max.y.max(header.bounds.max.y);
+                bounds.max.z = bounds.max.z.max(header.bounds.max.z);
+                bounds
+            }
+        });
+
+        if let Some(date) = header.date {
+            self.first_date = Some(self.first_date.map_or(date, |first| first.min(date)));
+            self.last_date = Some(self.last_date.map_or(date, |last| last.max(date)));
+        }
+
+        let point_format = header
+            .point_format
+            .to_u8()
+            .map_or(String::from("unknown"), |n| n.to_string());
+        *self.point_formats.entry(point_format).or_default() += 1;
+        *self.versions.entry(header.version_name()).or_default() += 1;
+        *self
+            .generating_softwares
+            .entry(header.generating_software.clone())
+            .or_default() += 1;
+    }
+
+    pub fn to_text(&self) -> String {
+        let histogram = |title: &str, counts: &BTreeMap<String, u64>| {
+            let mut lines = vec![format!("{title}:")];
+            for (value, count) in counts {
+                lines.push(format!("  {value}: {count}"));
+            }
+            lines.join("\n")
+        };
+
+        let mut lines = vec![
+            format!("Files:           {}", self.file_count),
+            format!("Points:          {}", self.total_points),
+            format!("Bytes:           {}", self.total_bytes),
+        ];
+        if let Some(bounds) = self.bounds {
+            lines.push(format!(
+                "Min x y z:       {} {} {}",
+                bounds.min.x, bounds.min.y, bounds.min.z
+            ));
+            lines.push(format!(
+                "Max x y z:       {} {} {}",
+                bounds.max.x, bounds.max.y, bounds.max.z
+            ));
+        }
+        if let (Some(first), Some(last)) = (self.first_date, self.last_date) {
+            lines.push(format!("Dates:           {first} to {last}"));
+        }
+        lines.push(histogram("Point formats", &self.point_formats));
+        lines.push(histogram("Versions", &self.versions));
+        lines.push(histogram(
+            "Generating softwares",
+            &self.generating_softwares,
+        ));
+
+        lines.join("\n")
+    }
+
+    /// Formats the totals as a single CSV row (histograms are only in the text and JSON reports).
+    pub fn to_csv(&self) -> String {
+        let bounds = self.bounds.map_or(vec![String::new(); 6], |b| {
+            [b.min.x, b.min.y, b.min.z, b.max.x, b.max.y, b.max.z]
+                .iter()
+                .map(|value| value.to_string())
+                .collect()
+        });
+        let date = |date: Option<NaiveDate>| date.map_or(String::new(), |date| date.to_string());
+
+        let header = "file_count,total_points,total_bytes,min_x,min_y,min_z,max_x,max_y,max_z,\
+            first_date,last_date";
+        let row = [
+            vec![
+                self.file_count.to_string(),
+                self.total_points.to_string(),
+                self.total_bytes.to_string(),
+            ],
+            bounds,
+            vec![date(self.first_date), date(self.last_date)],
+        ]
+        .concat();
+
+        format!("{header}\n{}", row.join(","))
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "file_count": self.file_count,
+            "total_points": self.total_points,
+            "total_bytes": self.total_bytes,
+            "min": self.bounds.map(|b| [b.min.x, b.min.y, b.min.z]),
+            "max": self.bounds.map(|b| [b.max.x, b.max.y, b.max.z]),
+            "first_date": self.first_date.map(|date| date.to_string()),
+            "last_date": self.last_date.map(|date| date.to_string()),
+            "point_formats": self.point_formats,
+            "versions": self.versions,
+            "generating_softwares": self.generating_softwares,
+        })
+    }
+}
+
+#[test]
+fn test_stats() {
+    let mut builder = las::Builder::from((1, 4));
+    builder.date = NaiveDate::from_ymd_opt(2023, 5, 2);
+    let header = MyHeader::new(&builder.into_header().unwrap());
+
+    let mut stats = Stats::default();
+    stats.add(&header, 100);
+    stats.add(&header, 50);
+
+    assert_eq!(stats.file_count, 2);
+    assert_eq!(stats.total_bytes, 150);
+    assert_eq!(stats.versions.get("1.4"), Some(&2));
+    assert_eq!(stats.first_date, NaiveDate::from_ymd_opt(2023, 5, 2));
+}
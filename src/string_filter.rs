@@ -1,49 +1,108 @@
 use crate::filter::Compare;
 use crate::header::MyHeader;
+use crate::query::quote;
+use std::fmt::{Display, Formatter};
 
 use regex::Regex;
 use semver::VersionReq;
 
-#[derive(Debug)]
+/// Condition of a string filter, parsed once when the filter is built.
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// Gps time type, true for the standard one
+    GpsTimeType(bool),
+    Version(VersionReq),
+    Pattern(Regex),
+}
+
+#[derive(Debug, Clone)]
 pub struct StringFilter {
     value: String,
+    matcher: Matcher,
     header_attribute: String,
 }
 
 impl StringFilter {
-    pub fn new(input_string: &String, attribute_name: &String) -> StringFilter {
-        StringFilter {
+    /// Parses the condition: "week" or "standard" for the gps time type, a semver requirement
+    /// for the las version and a regex for the other attributes.
+    pub fn new(input_string: &str, attribute_name: &str) -> Result<StringFilter, String> {
+        let matcher = match attribute_name {
+            "gps_time_type" => match input_string {
+                "week" => Matcher::GpsTimeType(false),
+                "standard" => Matcher::GpsTimeType(true),
+                _ => return Err(format!("unknown gps time type \"{input_string}\"")),
+            },
+            "version" => VersionReq::parse(input_string)
+                .map(Matcher::Version)
+                .map_err(|error| format!("invalid version for {attribute_name}: {error}"))?,
+            _ => Regex::new(input_string)
+                .map(Matcher::Pattern)
+                .map_err(|error| format!("invalid regex for {attribute_name}: {error}"))?,
+        };
+        Ok(StringFilter {
             value: input_string.to_string(),
+            matcher,
             header_attribute: attribute_name.to_string(),
-        }
+        })
     }
 }
 
 impl Compare for StringFilter {
-    fn compare(&self, value: &MyHeader) -> bool {
+    fn attribute(&self) -> &str {
+        &self.header_attribute
+    }
+
+    fn header_value(&self, value: &MyHeader) -> String {
         match self.header_attribute.as_str() {
-            "gps_time_type" => match self.value.as_str() {
-                "week" => !value.gps_time_type.is_standard(),
-                "standard" => value.gps_time_type.is_standard(),
-                _ => panic!("Gps time type error"),
-            },
-            "las_version" => {
-                let req = VersionReq::parse(&self.value).unwrap();
-                return req.matches(&value.version);
-            }
-            "guid" => {
-                let re = Regex::new(&self.value).unwrap();
-                re.is_match(&value.guid.to_string())
-            }
-            "generating_software" => {
-                let re = Regex::new(&self.value).unwrap();
+            "gps_time_type" => value.gps_time_type_name().to_string(),
+            "version" => value.version_name(),
+            "guid" => value.guid.to_string(),
+            "generating_software" => value.generating_software.clone(),
+            "system_identifier" => value.system_identifier.clone(),
+            attribute => value
+                .name_field(attribute)
+                .map_or(String::from("-"), str::to_string),
+        }
+    }
+
+    fn compare(&self, value: &MyHeader) -> bool {
+        match (&self.matcher, self.header_attribute.as_str()) {
+            (Matcher::GpsTimeType(standard), _) => value.gps_time_type.is_standard() == *standard,
+            (Matcher::Version(req), _) => req.matches(&value.version),
+            (Matcher::Pattern(re), "guid") => re.is_match(&value.guid.to_string()),
+            (Matcher::Pattern(re), "generating_software") => {
                 re.is_match(&value.generating_software)
             }
-            "system_identifier" => {
-                let re = Regex::new(&self.value).unwrap();
-                re.is_match(&value.system_identifier)
-            }
-            _ => false,
+            (Matcher::Pattern(re), "system_identifier") => re.is_match(&value.system_identifier),
+            (Matcher::Pattern(re), attribute) => value
+                .name_field(attribute)
+                .is_some_and(|field| re.is_match(field)),
         }
     }
 }
+
+impl Display for StringFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.header_attribute, quote(&self.value))
+    }
+}
+
+#[test]
+fn test_string_filter() {
+    let mut builder = las::Builder::from((1, 4));
+    builder.generating_software = String::from("TerraScan 21");
+    let mut header = MyHeader::new(&builder.into_header().unwrap());
+    header.name_fields = Some([(String::from("block"), String::from("A12"))].into());
+
+    let filter = |value: &str, attribute: &str| StringFilter::new(value, attribute);
+    assert!(filter("^Terra", "generating_software")
+        .unwrap()
+        .compare(&header));
+    assert!(filter(">=1.3", "version").unwrap().compare(&header));
+    assert!(filter("^A", "name.block").unwrap().compare(&header));
+    assert!(!filter("^A", "name.tile").unwrap().compare(&header));
+    assert!(filter("standard", "gps_time_type").is_ok());
+    assert!(filter("([", "generating_software").is_err());
+    assert!(filter("1.x.y.z", "version").is_err());
+    assert!(filter("daily", "gps_time_type").is_err());
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use las::Bounds;
+use regex::Regex;
+
+use crate::validate::Issue;
+
+/// Prefix of the attributes captured from the file names (eg. "name.x").
+pub const NAME_PREFIX: &str = "name.";
+
+/// Corner of a grid cell given by the coordinates of the tile names.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellOrigin {
+    LowerLeft,
+    UpperLeft,
+}
+
+impl FromStr for CellOrigin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lower-left" => Ok(CellOrigin::LowerLeft),
+            "upper-left" => Ok(CellOrigin::UpperLeft),
+            _ => Err(format!("Unknown cell origin \"{s}\"")),
+        }
+    }
+}
+
+/// Grid of the tiles, the "x" and "y" captures of a name locating its cell.
+#[derive(Debug, Clone)]
+pub struct TileGrid {
+    /// Size of a cell, in CRS units
+    pub size: f64,
+    /// Multiplier from the captured coordinates to CRS units (eg. 1000 for kilometers)
+    pub scale: f64,
+    pub origin: CellOrigin,
+}
+
+impl TileGrid {
+    /// Bounds (min x, min y, max x, max y) of the cell at the name coordinates.
+    pub fn cell(&self, x: f64, y: f64) -> (f64, f64, f64, f64) {
+        let (x, y) = (x * self.scale, y * self.scale);
+        match self.origin {
+            CellOrigin::LowerLeft => (x, y, x + self.size, y + self.size),
+            CellOrigin::UpperLeft => (x, y - self.size, x + self.size, y),
+        }
+    }
+}
+
+/// Pattern of the file names, whose named captures are attributes of the files.
+#[derive(Debug, Clone)]
+pub struct TileNames {
+    pub pattern: Regex,
+    /// Grid checked against the header bounds when validating
+    pub grid: Option<TileGrid>,
+}
+
+impl TileNames {
+    /// Compiles the pattern of the names, without grid.
+    pub fn new(pattern: &str) -> Result<TileNames, String> {
+        Ok(TileNames {
+            pattern: Regex::new(pattern)
+                .map_err(|error| format!("invalid name pattern: {error}"))?,
+            grid: None,
+        })
+    }
+
+    /// Returns the named captures of the file name, empty when it doesn't match the pattern.
+    pub fn captures(&self, path: &Path) -> BTreeMap<String, String> {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let Some(captures) = self.pattern.captures(&name) else {
+            return BTreeMap::new();
+        };
+        self.pattern
+            .capture_names()
+            .flatten()
+            .filter_map(|field| {
+                let value = captures.name(field)?;
+                Some((field.to_string(), value.as_str().to_string()))
+            })
+            .collect()
+    }
+
+    /// Checks that the bounds of a file fall inside the cell its name claims.
+    pub fn check(&self, fields: &BTreeMap<String, String>, bounds: &Bounds) -> Vec<Issue> {
+        let Some(grid) = &self.grid else {
+            return Vec::new();
+        };
+        let coordinate = |axis: &str| fields.get(axis).and_then(|v| v.parse::<f64>().ok());
+        let (Some(x), Some(y)) = (coordinate("x"), coordinate("y")) else {
+            let message = format!(
+                "no x and y coordinates in the file name (pattern \"{}\")",
+                self.pattern
+            );
+            return vec![Issue::new("name", message)];
+        };
+
+        // Writers leave the bounds of empty files undefined
+        if !(bounds.min.x.is_finite() && bounds.max.x.is_finite()) {
+            return Vec::new();
+        }
+        let (min_x, min_y, max_x, max_y) = grid.cell(x, y);
+        match bounds.min.x >= min_x
+            && bounds.min.y >= min_y
+            && bounds.max.x <= max_x
+            && bounds.max.y <= max_y
+        {
+            true => Vec::new(),
+            false => vec![Issue::new(
+                "name",
+                format!(
+                    "bounds {} {}, {} {} are outside the cell {min_x} {min_y}, {max_x} {max_y} \
+                    of the file name",
+                    bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y
+                ),
+            )],
+        }
+    }
+}
+
+#[test]
+fn test_tile_names() {
+    let names = TileNames {
+        pattern: Regex::new(r"_(?P<x>\d{4})_(?P<y>\d{4})_").unwrap(),
+        grid: Some(TileGrid {
+            size: 1000.0,
+            scale: 1000.0,
+            origin: CellOrigin::UpperLeft,
+        }),
+    };
+    let fields = names.captures(Path::new("/data/LHD_FXX_0650_6860_PTS_C_LAMB93.laz"));
+    assert_eq!(fields["x"], "0650");
+    assert_eq!(fields["y"], "6860");
+    assert!(names.captures(Path::new("tile.laz")).is_empty());
+
+    let bounds = |min_x: f64, max_y: f64| Bounds {
+        min: las::Vector {
+            x: min_x,
+            y: max_y - 1000.0,
+            z: 0.0,
+        },
+        max: las::Vector {
+            x: min_x + 1000.0,
+            y: max_y,
+            z: 0.0,
+        },
+    };
+    assert!(names
+        .check(&fields, &bounds(650000.0, 6860000.0))
+        .is_empty());
+    assert_eq!(
+        names.check(&fields, &bounds(651000.0, 6860000.0))[0].check,
+        "name"
+    );
+}
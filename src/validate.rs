@@ -0,0 +1,376 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Local, NaiveDate};
+use las::raw;
+use serde_json::{json, Value};
+
+use crate::group::csv_field;
+use crate::list_filter::COMPRESSION_MASK;
+
+/// An inconsistency of a header, with the LAS specification or with the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// Name of the failed check (point_count, bounds, legacy_counts, point_format, date, name...)
+    pub check: &'static str,
+    pub message: String,
+}
+
+impl Issue {
+    pub fn new(check: &'static str, message: String) -> Issue {
+        Issue { check, message }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "check": self.check, "message": self.message })
+    }
+}
+
+/// Reads the raw header of a file and checks it.
+pub fn validate_path(path: &Path) -> Result<Vec<Issue>, String> {
+    let read_error =
+        |error: &dyn std::fmt::Display| format!("Impossible to read {}: {error}", path.display());
+    let file = File::open(path).map_err(|error| read_error(&error))?;
+    let file_size = file.metadata().map_err(|error| read_error(&error))?.len();
+    let header =
+        raw::Header::read_from(BufReader::new(file)).map_err(|error| read_error(&error))?;
+    Ok(validate(&header, file_size))
+}
+
+/// Checks a raw header against the LAS specification and against the size of its file.
+pub fn validate(header: &raw::Header, file_size: u64) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    check_point_format(header, &mut issues);
+    check_point_count(header, file_size, &mut issues);
+    check_legacy_counts(header, &mut issues);
+    check_bounds(header, &mut issues);
+    check_date(header, &mut issues);
+    issues
+}
+
+fn number_of_points(header: &raw::Header) -> u64 {
+    header
+        .large_file
+        .map_or(header.number_of_point_records as u64, |large_file| {
+            large_file.number_of_point_records
+        })
+}
+
+/// Checks the point format against the version, and the record length against the format.
+fn check_point_format(header: &raw::Header, issues: &mut Vec<Issue>) {
+    let format = header.point_data_record_format & !COMPRESSION_MASK;
+    let max_format = match header.version.minor {
+        0 | 1 => 1,
+        2 => 3,
+        3 => 5,
+        _ => 10,
+    };
+    if format > max_format {
+        issues.push(Issue::new(
+            "point_format",
+            format!(
+                "point format {format} is not allowed in version {}",
+                header.version
+            ),
+        ));
+    }
+
+    if let Ok(point_format) = las::point::Format::new(format) {
+        if header.point_data_record_length < point_format.len() {
+            issues.push(Issue::new(
+                "point_format",
+                format!(
+                    "point record length {} is less than the {} bytes of point format {format}",
+                    header.point_data_record_length,
+                    point_format.len()
+                ),
+            ));
+        }
+    }
+
+    if (header.offset_to_point_data as u64) < header.header_size as u64 {
+        issues.push(Issue::new(
+            "point_format",
+            format!(
+                "offset to point data {} is inside the {} bytes header",
+                header.offset_to_point_data, header.header_size
+            ),
+        ));
+    }
+}
+
+/// Checks the declared number of points against the size of the (uncompressed) file.
+fn check_point_count(header: &raw::Header, file_size: u64, issues: &mut Vec<Issue>) {
+    let number_of_points = number_of_points(header);
+    let by_return: u64 = match header.large_file {
+        Some(large_file) => large_file.number_of_points_by_return.iter().sum(),
+        None => header
+            .number_of_points_by_return
+            .iter()
+            .map(|&n| n as u64)
+            .sum(),
+    };
+    if by_return > 0 && by_return != number_of_points {
+        issues.push(Issue::new(
+            "point_count",
+            format!("{by_return} points by return, for {number_of_points} points"),
+        ));
+    }
+
+    if header.point_data_record_format & COMPRESSION_MASK != 0 {
+        return;
+    }
+    let points_end = header.offset_to_point_data as u64
+        + number_of_points * header.point_data_record_length as u64;
+    let (expected, what) = match header.evlr {
+        Some(evlr) if evlr.number_of_evlrs > 0 => (evlr.start_of_first_evlr, "the first EVLR at"),
+        _ => (file_size, "a file of"),
+    };
+    if points_end != expected {
+        issues.push(Issue::new(
+            "point_count",
+            format!(
+                "{number_of_points} points of {} bytes from offset {} end at {points_end}, \
+                for {what} {expected} bytes",
+                header.point_data_record_length, header.offset_to_point_data
+            ),
+        ));
+    }
+}
+
+/// Checks the legacy point counts of las 1.4 files against the extended ones.
+fn check_legacy_counts(header: &raw::Header, issues: &mut Vec<Issue>) {
+    let Some(large_file) = header.large_file else {
+        return;
+    };
+    let legacy_by_return = header.number_of_points_by_return.map(|n| n as u64);
+    let extended_by_return: [u64; 5] = large_file.number_of_points_by_return[..5]
+        .try_into()
+        .unwrap();
+
+    let format = header.point_data_record_format & !COMPRESSION_MASK;
+    let (expected_count, expected_by_return, reason) = match format {
+        // Legacy counts are zero for the formats which can't be read by legacy readers
+        6.. => (0, [0; 5], format!("with point format {format}")),
+        _ if large_file.number_of_point_records > u32::MAX as u64 => {
+            (0, [0; 5], String::from("with more than 2^32 points"))
+        }
+        _ => (
+            large_file.number_of_point_records,
+            extended_by_return,
+            String::from("as the extended counts"),
+        ),
+    };
+    if header.number_of_point_records as u64 != expected_count {
+        issues.push(Issue::new(
+            "legacy_counts",
+            format!(
+                "legacy number of points {} should be {expected_count} {reason}",
+                header.number_of_point_records
+            ),
+        ));
+    }
+    if legacy_by_return != expected_by_return {
+        issues.push(Issue::new(
+            "legacy_counts",
+            format!(
+                "legacy points by return {legacy_by_return:?} should be \
+                {expected_by_return:?} {reason}"
+            ),
+        ));
+    }
+}
+
+/// Checks that the bounds are ordered and can be stored with the scales and offsets.
+fn check_bounds(header: &raw::Header, issues: &mut Vec<Issue>) {
+    let axes = [
+        (
+            "x",
+            header.min_x,
+            header.max_x,
+            header.x_scale_factor,
+            header.x_offset,
+        ),
+        (
+            "y",
+            header.min_y,
+            header.max_y,
+            header.y_scale_factor,
+            header.y_offset,
+        ),
+        (
+            "z",
+            header.min_z,
+            header.max_z,
+            header.z_scale_factor,
+            header.z_offset,
+        ),
+    ];
+
+    // Writers leave the bounds of empty files undefined
+    if number_of_points(header) == 0 {
+        return;
+    }
+    for (axis, min, max, scale, offset) in axes {
+        if scale <= 0.0 || !scale.is_finite() {
+            issues.push(Issue::new(
+                "bounds",
+                format!("{axis} scale factor {scale} is not positive"),
+            ));
+            continue;
+        }
+        if min > max {
+            issues.push(Issue::new(
+                "bounds",
+                format!("min {axis} {min} is greater than max {axis} {max}"),
+            ));
+        }
+        for bound in [min, max] {
+            let quantized = (bound - offset) / scale;
+            if quantized < i32::MIN as f64 || quantized > i32::MAX as f64 {
+                issues.push(Issue::new(
+                    "bounds",
+                    format!(
+                        "{axis} bound {bound} can't be stored with scale {scale} and offset \
+                        {offset}"
+                    ),
+                ));
+            } else if (quantized - quantized.round()).abs() > 1e-3 {
+                issues.push(Issue::new(
+                    "bounds",
+                    format!(
+                        "{axis} bound {bound} is not a multiple of scale {scale} from offset \
+                        {offset}"
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Checks that the creation date, when it is set, is a valid past date.
+fn check_date(header: &raw::Header, issues: &mut Vec<Issue>) {
+    let (day, year) = (header.file_creation_day_of_year, header.file_creation_year);
+    if day == 0 && year == 0 {
+        return;
+    }
+    match NaiveDate::from_yo_opt(year as i32, day as u32) {
+        None => issues.push(Issue::new(
+            "date",
+            format!("day {day} of year {year} is not a valid date"),
+        )),
+        Some(date) if date.year() < 1990 || date > Local::now().date_naive() => issues.push(
+            Issue::new("date", format!("creation date {date} is not plausible")),
+        ),
+        Some(_) => {}
+    }
+}
+
+/// Issues of the validated files, for `lg validate`.
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub files: Vec<(PathBuf, Vec<Issue>)>,
+}
+
+impl ValidationReport {
+    pub fn add(&mut self, path: &Path, issues: &[Issue]) {
+        self.files.push((path.to_path_buf(), issues.to_vec()));
+    }
+
+    pub fn invalid_count(&self) -> usize {
+        self.files
+            .iter()
+            .filter(|(_, issues)| !issues.is_empty())
+            .count()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        for (path, issues) in &self.files {
+            match issues.len() {
+                0 => lines.push(format!("{}: valid", path.display())),
+                count => lines.push(format!("{}: {count} issue(s)", path.display())),
+            }
+            for issue in issues {
+                lines.push(format!("  {}: {}", issue.check, issue.message));
+            }
+        }
+        lines.push(format!(
+            "{} file(s), {} invalid",
+            self.files.len(),
+            self.invalid_count()
+        ));
+        lines.join("\n")
+    }
+
+    /// One row by issue, valid files having a single row without check.
+    pub fn to_csv(&self) -> String {
+        let mut rows = vec![String::from("path,valid,check,message")];
+        for (path, issues) in &self.files {
+            let path = csv_field(&path.display().to_string());
+            if issues.is_empty() {
+                rows.push(format!("{path},true,,"));
+            }
+            for issue in issues {
+                rows.push(format!(
+                    "{path},false,{},{}",
+                    issue.check,
+                    csv_field(&issue.message)
+                ));
+            }
+        }
+        rows.join("\n")
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::Array(
+            self.files
+                .iter()
+                .map(|(path, issues)| {
+                    json!({
+                        "path": path.display().to_string(),
+                        "valid": issues.is_empty(),
+                        "issues": issues.iter().map(Issue::to_json).collect::<Vec<_>>(),
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_validate() {
+    let mut builder = las::Builder::from((1, 4));
+    builder.point_format = las::point::Format::new(6).unwrap();
+    builder.date = NaiveDate::from_ymd_opt(2023, 3, 1);
+    let header = builder.into_header().unwrap().into_raw().unwrap();
+    let size = header.offset_to_point_data as u64;
+    assert_eq!(validate(&header, size), Vec::new());
+
+    let mut broken = header.clone();
+    broken.version = las::Version::new(1, 2);
+    assert_eq!(validate(&broken, size)[0].check, "point_format");
+
+    let mut broken = header.clone();
+    broken.point_data_record_format = 8;
+    broken.number_of_point_records = 10;
+    broken.large_file.as_mut().unwrap().number_of_point_records = 2;
+    (broken.min_x, broken.min_y, broken.min_z) = (0.0001, 0.0, 0.0);
+    (broken.max_x, broken.max_y, broken.max_z) = (1.0, 1.0, 1.0);
+    broken.file_creation_day_of_year = 400;
+    let checks: Vec<&str> = validate(&broken, size)
+        .iter()
+        .map(|issue| issue.check)
+        .collect();
+    assert_eq!(
+        checks,
+        [
+            "point_format",
+            "point_count",
+            "legacy_counts",
+            "bounds",
+            "date"
+        ]
+    );
+}
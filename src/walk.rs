@@ -0,0 +1,197 @@
+use std::path::{Path, PathBuf};
+
+use walkdir::{DirEntry, WalkDir};
+
+use crate::filter::LasHeaderFilter;
+use crate::footprint::read_sidecar;
+use crate::header::MyHeader;
+use crate::tile_name::TileNames;
+use crate::validate::validate_path;
+
+/// A file selected by the filters.
+#[derive(Clone)]
+pub struct MatchedFile {
+    /// Index of the input the file was found in, when several inputs are walked
+    pub input: usize,
+    /// Input the file was found in
+    pub root: PathBuf,
+    pub path: PathBuf,
+    /// Size of the file, in bytes
+    pub size: u64,
+    pub header: MyHeader,
+}
+
+/// Options of the walk over the inputs.
+#[derive(Debug, Clone)]
+pub struct WalkOptions {
+    pub recursive: bool,
+    pub follow_links: bool,
+    /// Selects the files rejected by the filters
+    pub inverse: bool,
+    /// Lowercase extensions of the files read
+    pub extensions: Vec<String>,
+    /// Scans one point every n points of the files, to filter them on their points
+    pub scan_points: Option<u64>,
+    /// Validates the headers, to filter them on their validity
+    pub validate: bool,
+    /// Reads the sidecar footprints of the files
+    pub sidecars: bool,
+    /// Captures attributes from the file names, and checks them against the bounds when validating
+    pub names: Option<TileNames>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        WalkOptions {
+            recursive: false,
+            follow_links: false,
+            inverse: false,
+            extensions: vec![String::from("las"), String::from("laz")],
+            scan_points: None,
+            validate: false,
+            sidecars: false,
+            names: None,
+        }
+    }
+}
+
+/// Iterator over the files of a folder, with their selection status.
+///
+/// Yields an error for the files which can't be read, or whose filtering fails.
+pub struct Walk<'a> {
+    input: usize,
+    root: PathBuf,
+    entries: walkdir::IntoIter,
+    options: &'a WalkOptions,
+    filter: &'a LasHeaderFilter,
+}
+
+impl Iterator for Walk<'_> {
+    type Item = Result<(MatchedFile, bool), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for entry in self.entries.by_ref() {
+            // Unreadable folders and missing inputs are errors, not empty selections
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => return Some(Err(error.to_string())),
+            };
+            if entry.metadata().is_ok_and(|metadata| metadata.is_file()) {
+                if let Some(mut result) = file_filter(entry, &self.root, self.options, self.filter)
+                {
+                    if let Ok((file, _)) = &mut result {
+                        file.input = self.input;
+                    }
+                    return Some(result);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Walk<'_> {
+    /// Sets the index of the input of the files, when several inputs are walked.
+    pub fn input(mut self, input: usize) -> Self {
+        self.input = input;
+        self
+    }
+}
+
+/// Reads and filters a file, returns `None` if its extension is not walked.
+fn file_filter(
+    entry: DirEntry,
+    root: &Path,
+    options: &WalkOptions,
+    filter: &LasHeaderFilter,
+) -> Option<Result<(MatchedFile, bool), String>> {
+    let extension = entry.path().extension()?.to_ascii_lowercase();
+    let extension = extension.to_str().unwrap_or_default();
+    if !options.extensions.iter().any(|e| e == extension) {
+        return None;
+    }
+
+    let path = entry.path();
+    let result = read_header(path, options).and_then(|header| match filter.try_filter(&header) {
+        Ok(result) => Ok((
+            MatchedFile {
+                input: 0,
+                root: root.to_path_buf(),
+                path: path.to_path_buf(),
+                size: entry.metadata().map_or(0, |m| m.len()),
+                header,
+            },
+            options.inverse ^ result,
+        )),
+        Err(error) => Err(format!("{}: {error}", path.display())),
+    });
+    Some(result)
+}
+
+/// Reads the header of a file, with its points, sidecar footprint, name fields and issues when
+/// the options ask for them.
+pub fn read_header(path: &Path, options: &WalkOptions) -> Result<MyHeader, String> {
+    let header = match options.scan_points {
+        Some(every) => MyHeader::from_path_scanned(path, every),
+        None => MyHeader::from_path(path),
+    };
+    let header = match options.sidecars {
+        true => header.and_then(|mut header| {
+            header.sidecar = read_sidecar(path)?;
+            Ok(header)
+        }),
+        false => header,
+    };
+    let header = match &options.names {
+        Some(names) => header.map(|mut header| {
+            header.name_fields = Some(names.captures(path));
+            header
+        }),
+        None => header,
+    };
+    match options.validate {
+        true => header.and_then(|mut header| {
+            let mut issues = validate_path(path)?;
+            if let (Some(names), Some(fields)) = (&options.names, &header.name_fields) {
+                issues.extend(names.check(fields, &header.bounds));
+            }
+            header.issues = Some(issues);
+            Ok(header)
+        }),
+        false => header,
+    }
+}
+
+/// Walks a folder and filters its files.
+pub fn walk<'a>(
+    folder: impl AsRef<Path>,
+    options: &'a WalkOptions,
+    filter: &'a LasHeaderFilter,
+) -> Walk<'a> {
+    let folder = folder.as_ref();
+    let entries = WalkDir::new(folder)
+        .max_depth(if options.recursive { usize::MAX } else { 1 })
+        .follow_links(options.follow_links)
+        .into_iter();
+
+    Walk {
+        input: 0,
+        root: folder.to_path_buf(),
+        entries,
+        options,
+        filter,
+    }
+}
+
+/// Walks a folder and returns the selected files, along with the errors.
+pub fn find<'a>(
+    folder: impl AsRef<Path>,
+    options: &'a WalkOptions,
+    filter: &'a LasHeaderFilter,
+) -> impl Iterator<Item = Result<MatchedFile, String>> + 'a {
+    walk(folder, options, filter).filter_map(|result| match result {
+        Ok((file, true)) => Some(Ok(file)),
+        Ok((_, false)) => None,
+        Err(error) => Some(Err(error)),
+    })
+}
@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Writes two empty las files, generated by "lg" and "PDAL", in a new folder.
+fn delivery(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("lg-cli-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&folder).unwrap();
+    for software in ["lg", "PDAL"] {
+        let mut builder = las::Builder::default();
+        builder.generating_software = String::from(software);
+        let path = folder.join(format!("{software}.las"));
+        las::Writer::from_path(path, builder.into_header().unwrap()).unwrap();
+    }
+    folder
+}
+
+fn lg(folder: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_lg"))
+        .current_dir(folder)
+        .args(args)
+        .arg("--no-config")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_exit_status() {
+    let folder = delivery("status");
+
+    let selected = lg(&folder, &["."]);
+    let rejected = lg(&folder, &["--generating-software", "^TerraScan", "."]);
+    let missing = lg(&folder, &["missing"]);
+    std::fs::remove_dir_all(&folder).unwrap();
+
+    assert_eq!(selected.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&selected.stdout).lines().count(), 2);
+    assert_eq!(rejected.status.code(), Some(1));
+    assert!(rejected.stdout.is_empty());
+    assert_eq!(missing.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("missing"));
+}
+
+#[test]
+fn test_count_and_quiet() {
+    let folder = delivery("count");
+
+    let count = lg(&folder, &["-c", "."]);
+    let quiet = lg(&folder, &["-q", "--generating-software", "^lg$", "."]);
+    let quiet_rejected = lg(&folder, &["-q", "--generating-software", "^TerraScan", "."]);
+    std::fs::remove_dir_all(&folder).unwrap();
+
+    assert_eq!(count.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&count.stdout).trim(), "2");
+    assert_eq!(quiet.status.code(), Some(0));
+    assert!(quiet.stdout.is_empty());
+    assert_eq!(quiet_rejected.status.code(), Some(1));
+}
+
+#[test]
+fn test_info_errors() {
+    let folder = delivery("info");
+    std::fs::write(folder.join("corrupt.laz"), "not a laz file").unwrap();
+
+    let read = lg(&folder, &["info", "lg.las"]);
+    let failed = lg(&folder, &["info", "missing.las", "corrupt.laz"]);
+    let partly = lg(&folder, &["info", "lg.las", "missing.las"]);
+    std::fs::remove_dir_all(&folder).unwrap();
+
+    assert_eq!(read.status.code(), Some(0));
+    assert_eq!(failed.status.code(), Some(2));
+    assert!(failed.stdout.is_empty());
+    assert_eq!(partly.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&partly.stdout).contains("lg.las"));
+}
+
+#[test]
+fn test_explain() {
+    let folder = delivery("explain");
+
+    let explain = lg(
+        &folder,
+        &["--explain", "--generating-software", "^lg$", "lg.las"],
+    );
+    std::fs::remove_dir_all(&folder).unwrap();
+
+    assert_eq!(explain.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&explain.stdout);
+    assert!(stdout.contains("lg.las: selected"), "{stdout}");
+    assert!(stdout.contains("| lg | match"), "{stdout}");
+}
+
+#[test]
+fn test_no_config() {
+    let folder = delivery("config");
+    std::fs::write(folder.join(".lg.toml"), "[defaults\n").unwrap();
+
+    let without = Command::new(env!("CARGO_BIN_EXE_lg"))
+        .current_dir(&folder)
+        .arg(".")
+        .output()
+        .unwrap();
+    let ignored = lg(&folder, &["."]);
+    std::fs::remove_dir_all(&folder).unwrap();
+
+    assert_eq!(without.status.code(), Some(2));
+    assert_eq!(ignored.status.code(), Some(0));
+}